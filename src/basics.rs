@@ -4,20 +4,38 @@
 
 // Vars
 /// Private struct to locate the generator.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
 }
 
 /// Private enum for directions.
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum OrdinalDirections {
     North,
     East,
     South,
     West,
 }
+
+impl OrdinalDirections {
+    /// All directions, clockwise from North.
+    pub const ALL: [OrdinalDirections; 4] = [
+        OrdinalDirections::North, OrdinalDirections::East, 
+        OrdinalDirections::South, OrdinalDirections::West,
+    ];
+
+    /// Relative cords of one step in this direction.
+    pub fn offset(&self) -> Position {
+        match self {
+            OrdinalDirections::North => Position { x: 0, y: 1 },
+            OrdinalDirections::East => Position { x: 1, y: 0 },
+            OrdinalDirections::South => Position { x: 0, y: -1 },
+            OrdinalDirections::West => Position { x: -1, y: 0 },
+        }
+    }
+}
 /// Relative cords for neighbours in a arc of radius 1, the field of view pointing Y.
 pub const NEIGHBOURS_ARC_Y_1: [Position; 5] = [
     Position { x: -1, y: 0}, Position { x: -1, y: 1}, 
@@ -39,19 +57,411 @@ pub const NEIGHBOURS_ARC_YI_1: [Position; 2] = [
     Position { x : 1, y : 0}, Position { x : -1, y : 0}
 ];
 
+/// Shape of the tiles of a labyrinth, stored in the square grid either way.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tessellation {
+    Squares,
+    /// Hexagons in rows, odd rows shifted East by half a tile.
+    Hexagons,
+}
+
+/// Directions between hexagonal tiles, counterclockwise from East.
+#[derive(Clone, Copy, PartialEq)]
+pub enum HexDirections {
+    East,
+    NorthEast,
+    NorthWest,
+    West,
+    SouthWest,
+    SouthEast,
+}
+
+impl HexDirections {
+    pub const ALL: [HexDirections; 6] = [
+        HexDirections::East, HexDirections::NorthEast, HexDirections::NorthWest,
+        HexDirections::West, HexDirections::SouthWest, HexDirections::SouthEast,
+    ];
+
+    pub fn opposite(&self) -> HexDirections {
+        match self {
+            HexDirections::East => HexDirections::West,
+            HexDirections::NorthEast => HexDirections::SouthWest,
+            HexDirections::NorthWest => HexDirections::SouthEast,
+            HexDirections::West => HexDirections::East,
+            HexDirections::SouthWest => HexDirections::NorthEast,
+            HexDirections::SouthEast => HexDirections::NorthWest,
+        }
+    }
+
+    /// Tile next to `tile` in this direction; the offset depends on the parity of the row.
+    pub fn step(&self, tile: Position) -> Position {
+        let row_odd: i32 = tile.y.rem_euclid(2);
+        match self {
+            HexDirections::East => Position { x: tile.x + 1, y: tile.y },
+            HexDirections::West => Position { x: tile.x - 1, y: tile.y },
+            HexDirections::NorthEast => Position { x: tile.x + row_odd, y: tile.y + 1 },
+            HexDirections::NorthWest => Position { x: tile.x + row_odd - 1, y: tile.y + 1 },
+            HexDirections::SouthEast => Position { x: tile.x + row_odd, y: tile.y - 1 },
+            HexDirections::SouthWest => Position { x: tile.x + row_odd - 1, y: tile.y - 1 },
+        }
+    }
+
+    /// Field of view after a step in this direction: every neighbour of the new tile but the one left behind.
+    /// The hexagonal `NEIGHBOURS_ARC_*`; the two sides are also neighbours of the tile left behind.
+    pub fn arc(&self) -> [HexDirections; 5] {
+        let mut arc: [HexDirections; 5] = [*self; 5];
+        let back: HexDirections = self.opposite();
+        for (slot, direction) in arc.iter_mut().zip(HexDirections::ALL.iter().filter(|direction| **direction != back)) {
+            *slot = *direction;
+        }
+        arc
+    }
+}
+
+/// Where the generator starts, and the entrance is.
+#[derive(Clone, Copy, PartialEq)]
+pub enum StartPosition {
+    Centre,
+    /// First path tile, at the minimum of x and y.
+    Corner,
+    /// Random, from the seed.
+    Random,
+    /// Tile cords, clamped inside the grid.
+    At(Position),
+    /// Next to the middle of a side; the opening there is the entrance.
+    Side(OrdinalDirections),
+}
+
+/// Sides of the labyrinth, for the openings in its outer wall.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub struct Sides {
+    pub north: bool,
+    pub east: bool,
+    pub south: bool,
+    pub west: bool,
+}
+
+impl Sides {
+    /// Read sides from their initials, like "WE".
+    pub fn parse(text: &str) -> Sides {
+        let text: String = text.to_uppercase();
+        Sides {
+            north: text.contains('N'),
+            east: text.contains('E'),
+            south: text.contains('S'),
+            west: text.contains('W'),
+        }
+    }
+
+    /// The chosen sides, as the directions pointing outward.
+    pub fn directions(&self) -> Vec<OrdinalDirections> {
+        [(self.north, OrdinalDirections::North), (self.east, OrdinalDirections::East), (self.south, OrdinalDirections::South), (self.west, OrdinalDirections::West)]
+            .into_iter()
+            .filter(|(chosen, _)| *chosen)
+            .map(|(_, direction)| direction)
+            .collect()
+    }
+}
+
+/// Built-in silhouettes for `Mask::shape`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum MaskShape {
+    Circle,
+    Heart,
+    Diamond,
+}
+
+impl MaskShape {
+    pub fn parse(text: &str) -> Option<MaskShape> {
+        match text.trim().to_lowercase().as_str() {
+            "circle" => Some(MaskShape::Circle),
+            "heart" => Some(MaskShape::Heart),
+            "diamond" => Some(MaskShape::Diamond),
+            _ => None,
+        }
+    }
+}
+
+/// # Silhouette of the labyrinth, one flag per tile.
+/// Tiles outside of it are seen as `Void`, like the outside of the grid, so the generators keep the labyrinth inside.
+#[derive(Clone)]
+pub struct Mask {
+    pub width: usize,
+    pub height: usize,
+    /// Indexed by `y * width + x`, y pointing North.
+    inside: Vec<bool>,
+}
+
+impl Mask {
+    /// Built-in silhouette filling `width` by `height` tiles.
+    pub fn shape(shape: MaskShape, width: usize, height: usize) -> Mask {
+        let mut inside: Vec<bool> = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                // Centre of the tile, from -1 to 1 on both axes.
+                let u: f32 = (x as f32 + 0.5f32) / width as f32 * 2f32 - 1f32;
+                let v: f32 = (y as f32 + 0.5f32) / height as f32 * 2f32 - 1f32;
+                inside.push(match shape {
+                    MaskShape::Circle => u * u + v * v <= 1f32,
+                    MaskShape::Diamond => u.abs() + v.abs() <= 1f32,
+                    // (x² + y² - 1)³ - x²y³ <= 0, spanning about [-1.14, 1.14] by [-1, 1.25].
+                    MaskShape::Heart => {
+                        let (u, v) = (u * 1.15f32, v * 1.15f32 + 0.1f32);
+                        (u * u + v * v - 1f32).powi(3) - u * u * v.powi(3) <= 0f32
+                    },
+                });
+            }
+        }
+        Mask { width, height, inside }
+    }
+
+    /// Read a mask from a plain PBM ("P1") image, black being inside; or from text art,
+    /// where every character but space, '.', '0' and '-' is inside. The first row is North.
+    pub fn parse(text: &str) -> Option<Mask> {
+        let rows: Vec<Vec<bool>> = if text.trim_start().starts_with("P1") {
+            let content: String = text
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or(""))
+                .collect::<Vec<&str>>()
+                .join("\n");
+            let mut tokens = content.split_whitespace().skip(1);
+            let width: usize = tokens.next()?.parse().ok()?;
+            let height: usize = tokens.next()?.parse().ok()?;
+            // Bits may or may not be separated by spaces.
+            let bits: Vec<bool> = tokens
+                .flat_map(|token| token.chars())
+                .map(|bit| bit == '1')
+                .take(width * height)
+                .collect();
+            if width == 0 || bits.len() < width * height {
+                return None;
+            }
+            bits.chunks(width).map(|row| row.to_vec()).collect()
+        } else {
+            text.lines()
+                .map(|line| line.trim_end().chars().map(|character| !matches!(character, ' ' | '.' | '0' | '-')).collect())
+                .collect()
+        };
+        let width: usize = rows.iter().map(|row| row.len()).max()?;
+        let height: usize = rows.len();
+        if width == 0 {
+            return None;
+        }
+        let mut inside: Vec<bool> = vec![false; width * height];
+        for (row_index, row) in rows.iter().enumerate() {
+            let y: usize = height - 1 - row_index;
+            for (x, flag) in row.iter().enumerate() {
+                inside[y * width + x] = *flag;
+            }
+        }
+        Some(Mask { width, height, inside })
+    }
+
+    /// Same silhouette stretched to `width` by `height` tiles, nearest neighbour.
+    pub fn scaled(&self, width: usize, height: usize) -> Mask {
+        let mut inside: Vec<bool> = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                inside.push(self.inside[(y * self.height / height) * self.width + x * self.width / width]);
+            }
+        }
+        Mask { width, height, inside }
+    }
+
+    /// Silhouette of `count` labyrinths of `width` by `height` tiles laid side by side, West to East, one column apart.
+    /// Each is inside `mask` if any, whole otherwise; the columns between them are outside.
+    pub fn side_by_side(mask: Option<&Mask>, width: usize, height: usize, count: usize) -> Mask {
+        let stride: usize = width + 1;
+        let width_all: usize = (stride * count).saturating_sub(1);
+        let mut inside: Vec<bool> = Vec::with_capacity(width_all * height);
+        for y in 0..height {
+            for x in 0..width_all {
+                let local: Position = Position { x: (x % stride) as i32, y: y as i32 };
+                inside.push(x % stride < width && mask.is_none_or(|mask| mask.contains(local)));
+            }
+        }
+        Mask { width: width_all, height, inside }
+    }
+
+    /// Text art of the silhouette, North first, '#' inside and '.' outside; read back by `parse`.
+    pub fn to_text(&self) -> String {
+        let mut text: String = String::with_capacity((self.width + 1) * self.height);
+        for y in (0..self.height).rev() {
+            for x in 0..self.width {
+                text.push(if self.inside[y * self.width + x] { '#' } else { '.' });
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// If the tile is inside the silhouette; false outside of the mask.
+    pub fn contains(&self, tile: Position) -> bool {
+        tile.x >= 0
+            && tile.y >= 0
+            && (tile.x as usize) < self.width
+            && (tile.y as usize) < self.height
+            && self.inside[tile.y as usize * self.width + tile.x as usize]
+    }
+
+    /// If the tile and its 8 neighbours are inside: room for a path and the walls around it.
+    pub fn surrounds(&self, tile: Position) -> bool {
+        (-1..=1).all(|offset_y| (-1..=1).all(|offset_x| self.contains(Position { x: tile.x + offset_x, y: tile.y + offset_y })))
+    }
+
+    /// Tile surrounded by the silhouette the nearest to `tile`, if any.
+    pub fn nearest(&self, tile: Position) -> Option<Position> {
+        (0..self.width * self.height)
+            .map(|index| Position { x: (index % self.width) as i32, y: (index / self.width) as i32 })
+            .filter(|candidate| self.surrounds(*candidate))
+            .min_by_key(|candidate| (candidate.x - tile.x).abs() + (candidate.y - tile.y).abs())
+    }
+}
+
 /// Behaviour of the engine when stuck
+#[derive(Clone, Copy, PartialEq)]
 pub enum StuckReaction {
     OneStepBack,
     RandomPosition,
 }
 
+/// Which cell of its active list a growing tree carves from; generalisation of `StuckReaction`.
+/// `Mix` picks one of the policies at each step, with the given weights (need not sum to 1).
+#[derive(Clone, Copy, PartialEq)]
+pub enum CellSelection {
+    Newest,
+    Oldest,
+    Random,
+    Middle,
+    Mix { newest: f32, oldest: f32, random: f32, middle: f32 },
+}
+
+impl CellSelection {
+    /// Read a policy name, or weighted names like "newest:75,random:25".
+    /// Weights can't be negative, nor all zero.
+    pub fn parse(text: &str) -> Option<CellSelection> {
+        let text: String = text.trim().to_lowercase();
+        match text.as_str() {
+            "newest" => return Some(CellSelection::Newest),
+            "oldest" => return Some(CellSelection::Oldest),
+            "random" => return Some(CellSelection::Random),
+            "middle" => return Some(CellSelection::Middle),
+            _ => {}
+        }
+        let (mut newest, mut oldest, mut random, mut middle) = (0f32, 0f32, 0f32, 0f32);
+        for part in text.split(',') {
+            let (name, weight) = part.split_once(':')?;
+            let weight: f32 = weight.trim().parse().ok()?;
+            if weight < 0f32 || !weight.is_finite() {
+                return None;
+            }
+            match name.trim() {
+                "newest" => newest += weight,
+                "oldest" => oldest += weight,
+                "random" => random += weight,
+                "middle" => middle += weight,
+                _ => return None,
+            }
+        }
+        if newest + oldest + random + middle <= 0f32 {
+            return None;
+        }
+        Some(CellSelection::Mix { newest, oldest, random, middle })
+    }
+}
+
+/// The stuck reactions of `random_memory_based` are two of the growing tree policies.
+impl From<StuckReaction> for CellSelection {
+    fn from(stuck_reaction: StuckReaction) -> Self {
+        match stuck_reaction {
+            StuckReaction::OneStepBack => CellSelection::Newest,
+            StuckReaction::RandomPosition => CellSelection::Random,
+        }
+    }
+}
+
+/// Life-like rule of the cave automaton, on walls: a wall is born, or survives, with these counts of wall neighbours (out of 8).
+#[derive(Clone, Copy, PartialEq)]
+pub struct CaveRule {
+    pub birth: [bool; 9],
+    pub survival: [bool; 9],
+}
+
+impl CaveRule {
+    /// Read a rule written like "B678/S345678".
+    pub fn parse(text: &str) -> Option<CaveRule> {
+        let mut rule: CaveRule = CaveRule { birth: [false; 9], survival: [false; 9] };
+        for part in text.trim().to_uppercase().split('/') {
+            let mut chars = part.chars();
+            let counts: &mut [bool; 9] = match chars.next()? {
+                'B' => &mut rule.birth,
+                'S' => &mut rule.survival,
+                _ => return None,
+            };
+            for digit in chars {
+                let count: usize = digit.to_digit(10)? as usize;
+                *counts.get_mut(count)? = true;
+            }
+        }
+        Some(rule)
+    }
+}
+
 /// Behaviour when the engine encouter a wall: can it join "properly" two paths.
+#[derive(Clone, Copy, PartialEq)]
 pub enum Islet {
     No,
     Yes(f32),
 }
 
+/// Bias of the walker's choice of direction: a weight per direction, and a probability to keep going straight.
+#[derive(Clone, Copy, PartialEq)]
+pub struct DirectionBias {
+    pub north: f32,
+    pub east: f32,
+    pub south: f32,
+    pub west: f32,
+    /// Probability to step again in the direction of the last step, when it is still free.
+    pub straight: f32,
+}
+
+impl DirectionBias {
+    /// Weights as `N,E,S,W`, then optionally the straight probability, like `4,1,4,1,0.5`.
+    /// Weights can't be negative, nor all zero; no value can be infinite or NaN.
+    pub fn parse(text: &str) -> Option<DirectionBias> {
+        let values: Vec<f32> = text
+            .split(',')
+            .map(|value| value.trim().parse::<f32>().ok().filter(|value| value.is_finite()))
+            .collect::<Option<Vec<f32>>>()?;
+        if !(4..=5).contains(&values.len()) || values[..4].iter().any(|weight| *weight < 0f32) || values[..4].iter().all(|weight| *weight == 0f32) {
+            return None;
+        }
+        Some(DirectionBias {
+            north: values[0],
+            east: values[1],
+            south: values[2],
+            west: values[3],
+            straight: values.get(4).copied().unwrap_or(0f32).clamp(0f32, 1f32),
+        })
+    }
+
+    pub fn weight(&self, direction: OrdinalDirections) -> f32 {
+        match direction {
+            OrdinalDirections::North => self.north,
+            OrdinalDirections::East => self.east,
+            OrdinalDirections::South => self.south,
+            OrdinalDirections::West => self.west,
+        }
+    }
+
+    /// If all directions weigh the same: the choice stays uniform.
+    pub fn is_uniform(&self) -> bool {
+        self.north == self.east && self.east == self.south && self.south == self.west
+    }
+}
+
 /// If the generator can completely ignore the pathing rules, and, if yes, the probability.
+#[derive(Clone, Copy, PartialEq)]
 pub enum Unsubordination {
     No,
     Yes(f32)
@@ -63,11 +473,395 @@ pub const DEFAULT_WRITE_TO_FILE: bool = false;
 pub const DEFAULT_SIZE: usize = 32;
 /// DEFAULT - Iteration limit
 pub const DEFAULT_ITERATION_LIMIT: usize = 0;
+/// DEFAULT - Start position, and entrance.
+pub const DEFAULT_START: StartPosition = StartPosition::Centre;
+/// DEFAULT - Openings in the outer wall.
+pub const DEFAULT_OPENINGS: Sides = Sides { north: false, east: false, south: false, west: false };
 /// DEFAULT - Generator behaviour, if allow to create islet by doings "bridges".
 pub const DEFAULT_ISLET: Islet = Islet::Yes(0.01f32);
+/// DEFAULT - Generator behaviour, probability to tunnel under a corridor instead of stopping in front of it.
+pub const DEFAULT_WEAVE: f32 = 0f32;
 /// DEFAULT - Generator behaviour, unsubordination or to ignore all rules of wall contact, except exits.
 pub const DEFAULT_UNSUBORDINATION: Unsubordination = Unsubordination::No;
+/// DEFAULT - Generator behaviour, no bias: uniform choice of direction.
+pub const DEFAULT_BIAS: DirectionBias = DirectionBias { north: 1f32, east: 1f32, south: 1f32, west: 1f32, straight: 0f32 };
 /// DEFAULT - Generator behaviour.
 pub const DEFAULT_STUCK: StuckReaction = StuckReaction::RandomPosition;
+/// DEFAULT - Growing tree behaviour, which active cell to carve from.
+pub const DEFAULT_CELL_SELECTION: CellSelection = CellSelection::Newest;
+/// DEFAULT - Recursive division behaviour, chambers up to this size in cells (on both sides) are left open as rooms.
+pub const DEFAULT_ROOM_SIZE: usize = 1;
+/// DEFAULT - Dungeon behaviour, attempts to place a room.
+pub const DEFAULT_DUNGEON_ROOMS: usize = 12;
+/// DEFAULT - Cave behaviour, rule of the automaton: B678/S345678.
+pub const DEFAULT_CAVE_RULE: CaveRule = CaveRule {
+    birth: [false, false, false, false, false, false, true, true, true],
+    survival: [false, false, false, true, true, true, true, true, true],
+};
+/// DEFAULT - Cave behaviour, probability of a tile to start as a wall.
+pub const DEFAULT_CAVE_FILL: f32 = 0.45f32;
+/// DEFAULT - Cave behaviour, steps of the automaton.
+pub const DEFAULT_CAVE_STEPS: usize = 5;
+/// DEFAULT - Levels of the labyrinth, stacked and joined by stairs.
+pub const DEFAULT_LEVELS: usize = 1;
+/// DEFAULT - Generator behaviour, probability to take stairs at each step, with more than one level.
+pub const DEFAULT_STAIRS: f32 = 0.05f32;
+/// DEFAULT - World, chunks kept loaded.
+pub const DEFAULT_WORLD_CACHE: usize = 64;
 /// DEFAULT - Generator behaviour.
-pub const DEFAULT_STATE: bool = false;
\ No newline at end of file
+pub const DEFAULT_STATE: bool = false;
+
+/// Runtime settings of a generator run. `Default` gives the `DEFAULT_*` values; setters can be chained as a builder.
+#[derive(Clone)]
+pub struct GeneratorConfig {
+    /// Tiles of the labyrinth along x.
+    pub width: usize,
+    /// Tiles of the labyrinth along y.
+    pub height: usize,
+    pub iteration_limit: usize,
+    pub seed: u64,
+    pub start: StartPosition,
+    pub openings: Sides,
+    /// Silhouette of `width` by `height` tiles; `None` for the whole rectangle.
+    pub mask: Option<Mask>,
+    pub stuck_reaction: StuckReaction,
+    pub islet: Islet,
+    pub weave: f32,
+    pub unsubordination: Unsubordination,
+    pub bias: DirectionBias,
+    pub cell_selection: CellSelection,
+    pub room_size: usize,
+    pub cave_rule: CaveRule,
+    pub cave_fill: f32,
+    pub cave_steps: usize,
+    pub dungeon_rooms: usize,
+    pub levels: usize,
+    pub stairs: f32,
+    pub default_state: bool,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            width: DEFAULT_SIZE,
+            height: DEFAULT_SIZE,
+            iteration_limit: DEFAULT_ITERATION_LIMIT,
+            seed: 0,
+            start: DEFAULT_START,
+            openings: DEFAULT_OPENINGS,
+            mask: None,
+            stuck_reaction: DEFAULT_STUCK,
+            islet: DEFAULT_ISLET,
+            weave: DEFAULT_WEAVE,
+            unsubordination: DEFAULT_UNSUBORDINATION,
+            bias: DEFAULT_BIAS,
+            cell_selection: DEFAULT_CELL_SELECTION,
+            room_size: DEFAULT_ROOM_SIZE,
+            cave_rule: DEFAULT_CAVE_RULE,
+            cave_fill: DEFAULT_CAVE_FILL,
+            cave_steps: DEFAULT_CAVE_STEPS,
+            dungeon_rooms: DEFAULT_DUNGEON_ROOMS,
+            levels: DEFAULT_LEVELS,
+            stairs: DEFAULT_STAIRS,
+            default_state: DEFAULT_STATE,
+        }
+    }
+}
+
+impl GeneratorConfig {
+    pub fn new() -> Self {
+        GeneratorConfig::default()
+    }
+
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn height(mut self, height: usize) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Zero to disable the limit.
+    pub fn iteration_limit(mut self, iteration_limit: usize) -> Self {
+        self.iteration_limit = iteration_limit;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn start(mut self, start: StartPosition) -> Self {
+        self.start = start;
+        self
+    }
+
+    pub fn openings(mut self, openings: Sides) -> Self {
+        self.openings = openings;
+        self
+    }
+
+    /// The mask is stretched to the size set so far: set the size first.
+    pub fn mask(mut self, mask: Option<Mask>) -> Self {
+        self.mask = mask.map(|mask| mask.scaled(self.width, self.height));
+        self
+    }
+
+    pub fn stuck_reaction(mut self, stuck_reaction: StuckReaction) -> Self {
+        self.stuck_reaction = stuck_reaction;
+        self
+    }
+
+    pub fn islet(mut self, islet: Islet) -> Self {
+        self.islet = islet;
+        self
+    }
+
+    /// Probability to cross under a straight corridor, where the islets did not join it. Zero to disable.
+    pub fn weave(mut self, weave: f32) -> Self {
+        self.weave = weave;
+        self
+    }
+
+    pub fn unsubordination(mut self, unsubordination: Unsubordination) -> Self {
+        self.unsubordination = unsubordination;
+        self
+    }
+
+    pub fn bias(mut self, bias: DirectionBias) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    pub fn cell_selection(mut self, cell_selection: CellSelection) -> Self {
+        self.cell_selection = cell_selection;
+        self
+    }
+
+    pub fn room_size(mut self, room_size: usize) -> Self {
+        self.room_size = room_size;
+        self
+    }
+
+    pub fn cave_rule(mut self, cave_rule: CaveRule) -> Self {
+        self.cave_rule = cave_rule;
+        self
+    }
+
+    pub fn cave_fill(mut self, cave_fill: f32) -> Self {
+        self.cave_fill = cave_fill;
+        self
+    }
+
+    pub fn cave_steps(mut self, cave_steps: usize) -> Self {
+        self.cave_steps = cave_steps;
+        self
+    }
+
+    /// Attempts to place a room; overlapping ones are dropped.
+    pub fn dungeon_rooms(mut self, dungeon_rooms: usize) -> Self {
+        self.dungeon_rooms = dungeon_rooms;
+        self
+    }
+
+    pub fn levels(mut self, levels: usize) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// Probability, at each step, to go up or down a level.
+    pub fn stairs(mut self, stairs: f32) -> Self {
+        self.stairs = stairs;
+        self
+    }
+
+    /// If the tile is inside the labyrinth: its `width` by `height` tiles, and the mask.
+    pub fn inside(&self, tile: Position) -> bool {
+        tile.x >= 0
+            && tile.y >= 0
+            && (tile.x as usize) < self.width
+            && (tile.y as usize) < self.height
+            && self.mask.as_ref().is_none_or(|mask| mask.contains(tile))
+    }
+}
+
+/// Every setting, one per line like "- Seed: 42", written with the syntax of the prompts so a run can be made again.
+/// The mask follows its line as text art, see `Mask::parse`.
+impl std::fmt::Display for GeneratorConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let start: String = match self.start {
+            StartPosition::Centre => String::from("C"),
+            StartPosition::Corner => String::from("K"),
+            StartPosition::Random => String::from("R"),
+            StartPosition::At(tile) => format!("{},{}", tile.x, tile.y),
+            StartPosition::Side(OrdinalDirections::North) => String::from("N"),
+            StartPosition::Side(OrdinalDirections::East) => String::from("E"),
+            StartPosition::Side(OrdinalDirections::South) => String::from("S"),
+            StartPosition::Side(OrdinalDirections::West) => String::from("W"),
+        };
+        let openings: String = [(self.openings.north, 'N'), (self.openings.east, 'E'), (self.openings.south, 'S'), (self.openings.west, 'W')]
+            .iter()
+            .filter(|(chosen, _)| *chosen)
+            .map(|(_, initial)| *initial)
+            .collect();
+        let islet: f32 = match self.islet { Islet::Yes(p) => p, Islet::No => 0f32 };
+        let unsubordination: f32 = match self.unsubordination { Unsubordination::Yes(p) => p, Unsubordination::No => 0f32 };
+        let cell_selection: String = match self.cell_selection {
+            CellSelection::Newest => String::from("newest"),
+            CellSelection::Oldest => String::from("oldest"),
+            CellSelection::Random => String::from("random"),
+            CellSelection::Middle => String::from("middle"),
+            CellSelection::Mix { newest, oldest, random, middle } => format!("newest:{},oldest:{},random:{},middle:{}", newest, oldest, random, middle),
+        };
+        let counts = |counts: &[bool; 9]| -> String {
+            (0..9).filter(|count| counts[*count]).map(|count| count.to_string()).collect()
+        };
+
+        writeln!(f, "- Width: {}\n- Height: {}", self.width, self.height)?;
+        writeln!(f, "- Iteration limit: {}\n- Seed: {}", self.iteration_limit, self.seed)?;
+        writeln!(f, "- Start: {}\n- Openings: {}", start, if openings.is_empty() { "none" } else { &openings })?;
+        writeln!(f, "- Islet: {}\n- Weave: {}", islet, self.weave)?;
+        writeln!(f, "- Stuck reaction: {}", match self.stuck_reaction { StuckReaction::OneStepBack => "OSB", StuckReaction::RandomPosition => "RP" })?;
+        writeln!(f, "- Unsubordination: {}", unsubordination)?;
+        writeln!(f, "- Direction weights: {},{},{},{},{}", self.bias.north, self.bias.east, self.bias.south, self.bias.west, self.bias.straight)?;
+        writeln!(f, "- Cell selection: {}\n- Room size: {}", cell_selection, self.room_size)?;
+        writeln!(f, "- Cave rule: B{}/S{}", counts(&self.cave_rule.birth), counts(&self.cave_rule.survival))?;
+        writeln!(f, "- Cave fill: {}\n- Cave steps: {}\n- Dungeon rooms: {}", self.cave_fill, self.cave_steps, self.dungeon_rooms)?;
+        writeln!(f, "- Levels: {}\n- Stairs: {}\n- Default state: {}", self.levels, self.stairs, self.default_state)?;
+        match &self.mask {
+            Some(mask) => write!(f, "- Mask:\n{}", mask.to_text()),
+            None => writeln!(f, "- Mask: none"),
+        }
+    }
+}
+
+/// # Disjoint-set (union-find) of indexes `0..len`.
+/// Path halving on `find`, union by size.
+pub struct DisjointSet {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl DisjointSet {
+    pub fn new(len: usize) -> Self {
+        DisjointSet {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+        }
+    }
+
+    /// Representative of the set containing `element`.
+    pub fn find(&mut self, mut element: usize) -> usize {
+        while self.parents[element] != element {
+            self.parents[element] = self.parents[self.parents[element]];
+            element = self.parents[element];
+        }
+        element
+    }
+
+    /// Merge the sets of `a` and `b`. Return false if they were already the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let mut root_a: usize = self.find(a);
+        let mut root_b: usize = self.find(b);
+        if root_a == root_b {
+            return false;
+        }
+        if self.sizes[root_a] < self.sizes[root_b] {
+            std::mem::swap(&mut root_a, &mut root_b);
+        }
+        self.parents[root_b] = root_a;
+        self.sizes[root_a] += self.sizes[root_b];
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_parse_text_art() {
+        let mask: Mask = Mask::parse("#.\n##\n").unwrap();
+        assert_eq!((mask.width, mask.height), (2, 2));
+        // The first row is North.
+        assert!(mask.contains(Position { x: 0, y: 1 }));
+        assert!(!mask.contains(Position { x: 1, y: 1 }));
+        assert!(mask.contains(Position { x: 1, y: 0 }));
+        assert!(!mask.contains(Position { x: 2, y: 0 }));
+        assert_eq!(mask.to_text(), "#.\n##\n");
+    }
+
+    #[test]
+    fn mask_parse_pbm() {
+        let mask: Mask = Mask::parse("P1\n# comment\n3 2\n1 0 1\n011").unwrap();
+        assert_eq!(mask.to_text(), "#.#\n.##\n");
+        assert!(Mask::parse("P1\n3 2\n1 0 1").is_none());
+        assert!(Mask::parse("").is_none());
+    }
+
+    #[test]
+    fn mask_scaled() {
+        let mask: Mask = Mask::parse("#.\n.#").unwrap().scaled(4, 2);
+        assert_eq!(mask.to_text(), "##..\n..##\n");
+    }
+
+    #[test]
+    fn mask_side_by_side() {
+        let mask: Mask = Mask::parse("#.\n##").unwrap();
+        assert_eq!(Mask::side_by_side(Some(&mask), 2, 2, 2).to_text(), "#..#.\n##.##\n");
+        assert_eq!(Mask::side_by_side(None, 1, 1, 3).to_text(), "#.#.#\n");
+    }
+
+    #[test]
+    fn cell_selection_parse() {
+        assert!(CellSelection::parse(" Oldest ") == Some(CellSelection::Oldest));
+        assert!(
+            CellSelection::parse("newest:75,random:25,newest:5")
+                == Some(CellSelection::Mix { newest: 80f32, oldest: 0f32, random: 25f32, middle: 0f32 })
+        );
+        assert!(CellSelection::parse("newest:0").is_none());
+        assert!(CellSelection::parse("newest:-1,random:2").is_none());
+        assert!(CellSelection::parse("newest:nan").is_none());
+        assert!(CellSelection::parse("latest:1").is_none());
+    }
+
+    #[test]
+    fn cave_rule_parse() {
+        let rule: CaveRule = CaveRule::parse("b678/S345678").unwrap();
+        assert_eq!(rule.birth, [false, false, false, false, false, false, true, true, true]);
+        assert_eq!(rule.survival, [false, false, false, true, true, true, true, true, true]);
+        assert!(CaveRule::parse("B9/S1").is_none());
+        assert!(CaveRule::parse("X3/S23").is_none());
+        assert!(CaveRule::parse("B3/S2a").is_none());
+    }
+
+    #[test]
+    fn direction_bias_parse() {
+        let bias: DirectionBias = DirectionBias::parse("4, 1,4,1").unwrap();
+        assert_eq!((bias.north, bias.east, bias.south, bias.west, bias.straight), (4f32, 1f32, 4f32, 1f32, 0f32));
+        assert!(!bias.is_uniform());
+        assert_eq!(DirectionBias::parse("1,1,1,1,2").unwrap().straight, 1f32);
+        assert!(DirectionBias::parse("1,1,1,1").unwrap().is_uniform());
+        assert!(DirectionBias::parse("1,1,1").is_none());
+        assert!(DirectionBias::parse("0,0,0,0").is_none());
+        assert!(DirectionBias::parse("1,-1,1,1").is_none());
+        assert!(DirectionBias::parse("1,inf,1,1").is_none());
+    }
+
+    #[test]
+    fn disjoint_set_union_find() {
+        let mut sets: DisjointSet = DisjointSet::new(5);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 4));
+        assert!(!sets.union(1, 0));
+        assert_eq!(sets.find(0), sets.find(1));
+        assert_ne!(sets.find(1), sets.find(3));
+        assert!(sets.union(1, 4));
+        assert_eq!(sets.find(0), sets.find(3));
+        assert_ne!(sets.find(2), sets.find(0));
+        assert!(!sets.union(3, 0));
+    }
+}
@@ -1,11 +1,12 @@
 // LABYRINTH
-// Write files
+// Read and write files
 
 use std::fs::{self, File};
-use std::io::{Write};
+use std::io::{Write, BufWriter};
 use std::path::PathBuf;
 use chrono::Utc;
 use regex::Regex;
+use super::basics;
 
 pub const LABYRINTH_DIRECTORY: &'static str = "./generated/";
 
@@ -20,8 +21,9 @@ pub fn directory_initialize(dir_path: &PathBuf) {
     
 }
 
-pub fn new_labyrinth(labyrinth_string: String) {
-    let regex_laby: Regex = Regex::new(r"laby[0-9]+.txt").unwrap();
+/// Path of the next labyrinth file, `laby<id>.<extension>` with the id after the biggest one already saved, whatever its extension.
+fn labyrinth_path_next(extension: &str) -> PathBuf {
+    let regex_laby: Regex = Regex::new(r"laby[0-9]+\.(txt|svg)").unwrap();
     let regex_number: Regex = Regex::new(r"[0-9]+").unwrap();
 
     // Check directory
@@ -67,9 +69,13 @@ pub fn new_labyrinth(labyrinth_string: String) {
         }
     }
 
+    let path_string: String = format!("{}laby{}.{}", LABYRINTH_DIRECTORY, file_id, extension);
+    PathBuf::from(path_string)
+}
+
+pub fn new_labyrinth(labyrinth_string: String) {
     // Create the file
-    let path_string: String = format!("{}laby{}.txt", LABYRINTH_DIRECTORY, file_id);
-    let path: PathBuf = PathBuf::from(path_string);
+    let path: PathBuf = labyrinth_path_next("txt");
     let path_display: std::path::Display<'_> = path.display();
 
     let mut file = match File::create(&path) {
@@ -83,3 +89,60 @@ pub fn new_labyrinth(labyrinth_string: String) {
         Err(reason) => panic!("(X) file_handler.rs - Coulnd't write. Path: {}, Reason: {}", path_display, reason)
     };
 }
+
+/// Save a labyrinth given row by row, each row being written as soon as it is given; no need to hold the whole labyrinth in memory.
+pub fn new_labyrinth_stream<I: Iterator<Item = String>>(header: String, rows: I) {
+    let path: PathBuf = labyrinth_path_next("txt");
+    let path_display: std::path::Display<'_> = path.display();
+
+    let file = match File::create(&path) {
+        Ok(file) => file,
+        Err(reason) => panic!("(X) file_handler.rs - Couldn't create the log file. Path: {}, Reason: {}", path_display, reason)
+    };
+    let mut writer: BufWriter<File> = BufWriter::new(file);
+
+    let header: String = format!("Labyrinth. {}\n{}", Utc::now(), header);
+    let mut written: Result<(), std::io::Error> = writer.write_all(header.as_bytes());
+    for row in rows {
+        if written.is_err() {
+            break;
+        }
+        written = writer.write_all(row.as_bytes()).and_then(|_| writer.write_all(b"\n"));
+    }
+    match written.and_then(|_| writer.flush()) {
+        Ok(_) => println!("(+) file_handler.rs - Labyrinth saved in {}", path_display),
+        Err(reason) => panic!("(X) file_handler.rs - Coulnd't write. Path: {}, Reason: {}", path_display, reason)
+    };
+}
+
+/// Save a labyrinth drawn as an SVG image. Gives the path of the file.
+pub fn new_labyrinth_svg(svg: String) -> PathBuf {
+    let path: PathBuf = labyrinth_path_next("svg");
+    let path_display: std::path::Display<'_> = path.display();
+
+    let mut file = match File::create(&path) {
+        Ok(file) => file,
+        Err(reason) => panic!("(X) file_handler.rs - Couldn't create the log file. Path: {}, Reason: {}", path_display, reason)
+    };
+    match file.write_all(svg.as_bytes()) {
+        Ok(_) => println!("(+) file_handler.rs - Labyrinth saved in {}", path_display),
+        Err(reason) => panic!("(X) file_handler.rs - Coulnd't write. Path: {}, Reason: {}", path_display, reason)
+    };
+    path
+}
+
+/// Read a shape mask, from a plain PBM image or a text file; see `basics::Mask::parse`.
+pub fn read_mask(path: &str) -> Option<basics::Mask> {
+    let text: String = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(reason) => {
+            println!("(!) file_handler.rs - Couldn't read the mask. Path: {}, Reason: {}", path, reason);
+            return None;
+        }
+    };
+    let mask: Option<basics::Mask> = basics::Mask::parse(&text);
+    if mask.is_none() {
+        println!("(!) file_handler.rs - Not a mask, expected a plain PBM (P1) image or text art. Path: {}", path);
+    }
+    mask
+}
@@ -1,42 +1,546 @@
 // LABYRINTH
 // Generators
 
-use super::{HashMap, seq::IndexedRandom, rng, Rng};
-use super::{basics, ui, grid};
+use super::{HashMap, seq::{IndexedRandom, SliceRandom}, Rng, SeedableRng, StdRng};
+use super::{basics, ui, grid, topology, world};
+
+pub mod backtracker;
+pub mod prim;
+pub mod kruskal;
+pub mod uniform;
+pub mod eller;
+pub mod growing_tree;
+pub mod hunt_and_kill;
+pub mod binary_tree;
+pub mod division;
+pub mod cave;
+pub mod dungeon;
+pub mod hex;
+pub mod levels;
+
+/// # Common interface of all labyrinth algorithms.
+/// A generator takes a config and gives back the generated labyrinth.
+pub trait MazeGenerator {
+    /// Short name, used to select the generator.
+    fn name(&self) -> &'static str;
+    /// One line summary of the algorithm and its texture.
+    fn description(&self) -> &'static str;
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth;
+}
+
+/// # Generated labyrinth: its grid, and where its tagged tiles are.
+/// Lets the passes after generation, and the rendering, find the tagged tiles without searching the grid.
+/// The grid is square; only its first `width` by `height` tiles are the labyrinth.
+pub struct Labyrinth {
+    pub grid: grid::Grid,
+    pub width: usize,
+    pub height: usize,
+    pub entrance: basics::Position,
+    pub exits: Vec<basics::Position>,
+    /// Other tagged tiles, like rooms and doors.
+    pub tags: Vec<(basics::Position, grid::TileFeatures)>,
+    /// Silhouette of the labyrinth, its outside being `Void`.
+    pub mask: Option<basics::Mask>,
+    /// Shape of the tiles, for the passes after generation and the rendering.
+    pub tessellation: basics::Tessellation,
+}
+
+impl Labyrinth {
+    /// Labyrinth of the config's size, with an entrance, and no exit yet.
+    pub fn new(grid: grid::Grid, config: &basics::GeneratorConfig, entrance: basics::Position) -> Self {
+        Labyrinth {
+            grid,
+            width: config.width,
+            height: config.height,
+            entrance,
+            exits: Vec::new(),
+            tags: Vec::new(),
+            mask: config.mask.clone(),
+            tessellation: basics::Tessellation::Squares,
+        }
+    }
+
+    /// State of the tile, `Void` outside the labyrinth and its mask.
+    pub fn state_tile(&self, x: i32, y: i32) -> grid::TileState {
+        if x >= self.width as i32 
+            || y >= self.height as i32 
+            || self.mask.as_ref().is_some_and(|mask| !mask.contains(basics::Position { x, y })) 
+        {
+            return grid::TileState::Void;
+        }
+        self.grid.state_tile(x, y)
+    }
+
+    /// Biggest tile cords of the labyrinth.
+    pub fn tiles_max(&self) -> basics::Position {
+        basics::Position { x: self.width as i32 - 1, y: self.height as i32 - 1 }
+    }
+}
+
+/// Grid holding a labyrinth of `config.width` by `config.height` tiles. Grids are square: the tiles beyond are left in `state`, and seen as `Void` by `state_tile`.
+pub fn grid_initialize(config: &basics::GeneratorConfig, state: bool) -> grid::Grid {
+    grid::Grid::new(grid::GridKind::Squares, config.width.max(config.height), state)
+}
+
+/// State of the tile of the grid, `Void` outside the labyrinth of `config.width` by `config.height` tiles, and outside `config.mask`.
+pub fn state_tile(grid_labyrinth: &grid::Grid, config: &basics::GeneratorConfig, x: i32, y: i32) -> grid::TileState {
+    if x >= 0 && y >= 0 && !config.inside(basics::Position { x, y }) {
+        return grid::TileState::Void;
+    }
+    grid_labyrinth.state_tile(x, y)
+}
+
+/// Biggest tile cords of the labyrinth of the config.
+pub fn tiles_max(config: &basics::GeneratorConfig) -> basics::Position {
+    basics::Position { x: config.width as i32 - 1, y: config.height as i32 - 1 }
+}
+
+/// # Entry of the algorithms list, after the way the labyrinth is held.
+pub enum Algorithm {
+    /// Generator of a tile grid.
+    Tiles(Box<dyn MazeGenerator>),
+    /// Cells beyond the tile grid, carved by `topology::backtracker` and drawn as an SVG; the topology is sized after the config.
+    Cells {
+        name: &'static str,
+        description: &'static str,
+        topology: fn(&basics::GeneratorConfig) -> Box<dyn topology::Topology>,
+    },
+    /// Endless labyrinth, in chunks; see `world::World`.
+    World,
+}
+
+impl Algorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Tiles(generator) => generator.name(),
+            Algorithm::Cells { name, .. } => name,
+            Algorithm::World => world::NAME,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Algorithm::Tiles(generator) => generator.description(),
+            Algorithm::Cells { description, .. } => description,
+            Algorithm::World => world::DESCRIPTION,
+        }
+    }
+}
+
+/// All available algorithms, the first one being the default.
+pub fn registry() -> Vec<Algorithm> {
+    vec![
+        Algorithm::Tiles(Box::new(RandomMemoryBased)),
+        Algorithm::Tiles(Box::new(backtracker::Backtracker)),
+        Algorithm::Tiles(Box::new(prim::Prim)),
+        Algorithm::Tiles(Box::new(kruskal::Kruskal)),
+        Algorithm::Tiles(Box::new(uniform::Wilson)),
+        Algorithm::Tiles(Box::new(uniform::AldousBroder)),
+        Algorithm::Tiles(Box::new(eller::Eller)),
+        Algorithm::Tiles(Box::new(growing_tree::GrowingTree)),
+        Algorithm::Tiles(Box::new(hunt_and_kill::HuntAndKill)),
+        Algorithm::Tiles(Box::new(binary_tree::BinaryTree)),
+        Algorithm::Tiles(Box::new(binary_tree::Sidewinder)),
+        Algorithm::Tiles(Box::new(division::RecursiveDivision)),
+        Algorithm::Tiles(Box::new(cave::Cave)),
+        Algorithm::Tiles(Box::new(dungeon::Dungeon)),
+        Algorithm::Tiles(Box::new(hex::HexMemoryBased)),
+        Algorithm::Cells {
+            name: "delta",
+            description: "Backtracker on triangles; delta labyrinth, SVG output.",
+            topology: topology::delta,
+        },
+        Algorithm::Cells {
+            name: "theta",
+            description: "Backtracker on concentric rings, from the centre; theta labyrinth, SVG output.",
+            topology: topology::theta,
+        },
+        Algorithm::World,
+    ]
+}
+
+/// Find an algorithm of the registry by its name.
+pub fn find(name: &str) -> Option<Algorithm> {
+    registry()
+        .into_iter()
+        .find(|algorithm| algorithm.name() == name)
+}
+
+/// # Cell lattice of a grid, for the "perfect maze" generators.
+/// Cells are the tiles at odd coordinates; the tiles between two cells are walls that can be carved.
+/// With a mask, only the cells surrounded by the silhouette, walls included, are in the lattice.
+#[derive(Clone)]
+pub struct Lattice {
+    pub width: usize,
+    pub height: usize,
+    pub mask: Option<basics::Mask>,
+}
+
+impl Lattice {
+    /// Biggest lattice fitting in `width` by `height` tiles, keeping a wall on every border.
+    pub fn of_size(width: usize, height: usize) -> Lattice {
+        Lattice {
+            width: width.saturating_sub(1) / 2,
+            height: height.saturating_sub(1) / 2,
+            mask: None,
+        }
+    }
+
+    /// Lattice of the config's labyrinth, within its mask.
+    pub fn of_config(config: &basics::GeneratorConfig) -> Lattice {
+        Lattice {
+            mask: config.mask.clone(),
+            ..Lattice::of_size(config.width, config.height)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, cell: basics::Position) -> bool {
+        cell.x >= 0 
+            && cell.y >= 0 
+            && (cell.x as usize) < self.width 
+            && (cell.y as usize) < self.height
+            && self.mask.as_ref().is_none_or(|mask| mask.surrounds(Lattice::tile(cell)))
+    }
+
+    /// Index of the cell in a flat vector of the lattice.
+    pub fn index(&self, cell: basics::Position) -> usize {
+        cell.y as usize * self.width + cell.x as usize
+    }
+
+    pub fn cell(&self, index: usize) -> basics::Position {
+        basics::Position { x: (index % self.width) as i32, y: (index / self.width) as i32 }
+    }
+
+    /// Cell holding the tile, or the nearest one in the lattice.
+    pub fn cell_at(&self, tile: basics::Position) -> basics::Position {
+        let cell: basics::Position = basics::Position {
+            x: (tile.x / 2).clamp(0, (self.width as i32 - 1).max(0)),
+            y: (tile.y / 2).clamp(0, (self.height as i32 - 1).max(0)),
+        };
+        if self.mask.is_none() || self.contains(cell) {
+            return cell;
+        }
+        (0..self.len())
+            .map(|index| self.cell(index))
+            .filter(|candidate| self.contains(*candidate))
+            .min_by_key(|candidate| (candidate.x - cell.x).abs() + (candidate.y - cell.y).abs())
+            .unwrap_or(cell)
+    }
+
+    /// Tile cords of the cell in the grid.
+    pub fn tile(cell: basics::Position) -> basics::Position {
+        basics::Position { x: cell.x * 2 + 1, y: cell.y * 2 + 1 }
+    }
+
+    /// Tile cords of the wall between two adjacent cells.
+    pub fn wall(cell_a: basics::Position, cell_b: basics::Position) -> basics::Position {
+        basics::Position { x: cell_a.x + cell_b.x + 1, y: cell_a.y + cell_b.y + 1 }
+    }
+
+    /// Adjacent cells inside the lattice, with the direction to reach them.
+    pub fn neighbours(&self, cell: basics::Position) -> Vec<(basics::OrdinalDirections, basics::Position)> {
+        basics::OrdinalDirections::ALL
+            .iter()
+            .map(|direction| {
+                let offset: basics::Position = direction.offset();
+                (*direction, basics::Position { x: cell.x + offset.x, y: cell.y + offset.y })
+            })
+            .filter(|(_, neighbour)| self.contains(*neighbour))
+            .collect()
+    }
+
+    /// Cells connected to `from` through the lattice, indexed like `index`. With a mask, the silhouette may leave cells out of reach.
+    pub fn reachable(&self, from: basics::Position) -> Vec<bool> {
+        let mut reached: Vec<bool> = vec![false; self.len()];
+        if !self.contains(from) {
+            return reached;
+        }
+        reached[self.index(from)] = true;
+        let mut stack: Vec<basics::Position> = vec![from];
+        while let Some(cell) = stack.pop() {
+            for (_, neighbour) in self.neighbours(cell) {
+                if !reached[self.index(neighbour)] {
+                    reached[self.index(neighbour)] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+        reached
+    }
+}
+
+/// Carve the cell as a path, with the given features.
+pub fn carve_cell(
+    grid_labyrinth: &mut grid::Grid, 
+    config: &basics::GeneratorConfig, 
+    cell: basics::Position, 
+    features: Vec<grid::TileFeatures>
+) {
+    let tile: basics::Position = Lattice::tile(cell);
+    grid_labyrinth.update_tile(tile.x, tile.y, !config.default_state, features);
+}
+
+/// Carve the wall between two adjacent cells, and the cell `to`. The cell `from` is expected to be carved already.
+pub fn carve_passage(
+    grid_labyrinth: &mut grid::Grid, 
+    config: &basics::GeneratorConfig, 
+    from: basics::Position, 
+    to: basics::Position
+) {
+    let wall: basics::Position = Lattice::wall(from, to);
+    grid_labyrinth.update_tile(wall.x, wall.y, !config.default_state, Vec::new());
+    carve_cell(grid_labyrinth, config, to, Vec::new());
+}
+
+/// Tile of the outer wall in the middle of the side, where its opening is: the same for labyrinths of the same size, so that they line up when tiled edge to edge.
+/// Always on an odd row or column, where the cells of a lattice are.
+pub fn side_middle(tiles_max: basics::Position, side: basics::OrdinalDirections) -> basics::Position {
+    let middle = |max: i32| if max / 2 % 2 == 1 { max / 2 } else { (max / 2 - 1).max(0) };
+    match side {
+        basics::OrdinalDirections::North => basics::Position { x: middle(tiles_max.x), y: tiles_max.y },
+        basics::OrdinalDirections::East => basics::Position { x: tiles_max.x, y: middle(tiles_max.y) },
+        basics::OrdinalDirections::South => basics::Position { x: middle(tiles_max.x), y: 0 },
+        basics::OrdinalDirections::West => basics::Position { x: 0, y: middle(tiles_max.y) },
+    }
+}
+
+/// Tile where the generator starts, according to `config.start`; moved to the nearest tile surrounded by the mask, if any.
+pub fn start_tile(config: &basics::GeneratorConfig) -> basics::Position {
+    let tiles_max: basics::Position = tiles_max(config);
+    let start: basics::Position = match config.start {
+        basics::StartPosition::Centre => basics::Position { x: tiles_max.x / 2, y: tiles_max.y / 2 },
+        basics::StartPosition::Corner => basics::Position { x: 1.min(tiles_max.x), y: 1.min(tiles_max.y) },
+        basics::StartPosition::Random => {
+            // Own stream, so that the generator keeps the seed as given.
+            let mut start_rng: StdRng = StdRng::seed_from_u64(config.seed.wrapping_add(2));
+            basics::Position { 
+                x: start_rng.random_range(1..tiles_max.x.max(2)).min(tiles_max.x), 
+                y: start_rng.random_range(1..tiles_max.y.max(2)).min(tiles_max.y),
+            }
+        },
+        basics::StartPosition::At(tile) => basics::Position { 
+            x: tile.x.clamp(0, tiles_max.x), 
+            y: tile.y.clamp(0, tiles_max.y),
+        },
+        // Inward of the opening of the side.
+        basics::StartPosition::Side(side) => {
+            let opening: basics::Position = side_middle(tiles_max, side);
+            let outward: basics::Position = side.offset();
+            basics::Position { 
+                x: (opening.x - outward.x).clamp(0, tiles_max.x), 
+                y: (opening.y - outward.y).clamp(0, tiles_max.y),
+            }
+        },
+    };
+    match &config.mask {
+        Some(mask) if !mask.surrounds(start) => mask.nearest(start).unwrap_or(start),
+        _ => start,
+    }
+}
+
+/// Grid filled with walls and its lattice, the entrance cell being carved and tagged at the start.
+pub fn lattice_initialize(config: &basics::GeneratorConfig) -> (grid::Grid, Lattice, basics::Position) {
+    let mut grid_labyrinth: grid::Grid = grid_initialize(config, config.default_state);
+    let lattice: Lattice = Lattice::of_config(config);
+    let entrance: basics::Position = lattice.cell_at(start_tile(config));
+    if !lattice.is_empty() {
+        carve_cell(&mut grid_labyrinth, config, entrance, vec![grid::TileFeatures::Named("Entrance")]);
+    }
+    (grid_labyrinth, lattice, entrance)
+}
+
+/// Fit a labyrinth carved on the lattice to its mask; nothing to do without one.
+/// Pieces left apart inside the silhouette are joined through walls between their cells, at random, one wall per pair of pieces, so a perfect maze stays perfect.
+/// Then only the cells reachable from the entrance and the walls between them are kept: tiles outside the silhouette, or cut off by it, are walled.
+pub fn lattice_mask_fit(
+    grid_labyrinth: &mut grid::Grid, 
+    config: &basics::GeneratorConfig, 
+    lattice: &Lattice, 
+    entrance: basics::Position, 
+    generator_rng: &mut StdRng
+) {
+    if lattice.mask.is_none() {
+        return;
+    }
+    let is_path = |grid_labyrinth: &grid::Grid, tile: basics::Position| matches!(grid_labyrinth.state_tile(tile.x, tile.y), grid::TileState::On);
+
+    // Pieces, from the walls carved between cells of the lattice.
+    let mut pieces: basics::DisjointSet = basics::DisjointSet::new(lattice.len());
+    let mut walls_left: Vec<(basics::Position, basics::Position)> = Vec::new();
+    for index in 0..lattice.len() {
+        let cell: basics::Position = lattice.cell(index);
+        if !lattice.contains(cell) || !is_path(grid_labyrinth, Lattice::tile(cell)) {
+            continue;
+        }
+        for (direction, neighbour) in lattice.neighbours(cell) {
+            if !matches!(direction, basics::OrdinalDirections::North | basics::OrdinalDirections::East) 
+                || !is_path(grid_labyrinth, Lattice::tile(neighbour)) 
+            {
+                continue;
+            }
+            if is_path(grid_labyrinth, Lattice::wall(cell, neighbour)) {
+                pieces.union(index, lattice.index(neighbour));
+            } else {
+                walls_left.push((cell, neighbour));
+            }
+        }
+    }
+    walls_left.shuffle(generator_rng);
+    let mut joined: usize = 0;
+    for (cell, neighbour) in walls_left {
+        if pieces.union(lattice.index(cell), lattice.index(neighbour)) {
+            let wall: basics::Position = Lattice::wall(cell, neighbour);
+            grid_labyrinth.update_tile(wall.x, wall.y, !config.default_state, Vec::new());
+            joined += 1usize;
+        }
+    }
+
+    // Cells of the entrance's part, and the walls and posts between them.
+    let reached: Vec<bool> = lattice.reachable(entrance);
+    let kept = |x: i32, y: i32| -> bool {
+        let cell_reached = |cell: basics::Position| lattice.contains(cell) && reached[lattice.index(cell)];
+        match (x % 2 == 1, y % 2 == 1) {
+            (true, true) => cell_reached(basics::Position { x: x / 2, y: y / 2 }),
+            (false, true) => cell_reached(basics::Position { x: x / 2 - 1, y: y / 2 }) && cell_reached(basics::Position { x: x / 2, y: y / 2 }),
+            (true, false) => cell_reached(basics::Position { x: x / 2, y: y / 2 - 1 }) && cell_reached(basics::Position { x: x / 2, y: y / 2 }),
+            // Posts, only carved in open rooms.
+            (false, false) => [(-1, -1), (-1, 0), (0, -1), (0, 0)]
+                .iter()
+                .all(|(dx, dy)| cell_reached(basics::Position { x: x / 2 + dx, y: y / 2 + dy })),
+        }
+    };
+    let mut walled: usize = 0;
+    for y in 0..=grid_labyrinth.size.y as i32 {
+        for x in 0..=grid_labyrinth.size.x as i32 {
+            if is_path(grid_labyrinth, basics::Position { x, y }) && !kept(x, y) {
+                grid_labyrinth.update_tile(x, y, config.default_state, Vec::new());
+                walled += 1usize;
+            }
+        }
+    }
+    ui::dp(format!("- Mask: {} pieces joined, {} tiles walled.\n", joined, walled), ui::DebugLogging::Minimal);
+}
+
+/// `MazeGenerator` of `random_memory_based`.
+pub struct RandomMemoryBased;
+
+impl MazeGenerator for RandomMemoryBased {
+    fn name(&self) -> &'static str {
+        "random_memory_based"
+    }
+
+    fn description(&self) -> &'static str {
+        "Random walker with a field of view, rewinding its memory when stuck; organic texture."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        random_memory_based(config, labyrinth_ui_features)
+    }
+}
 
 /// # Labyrinth generator; memory based, no recursion.
 /// Take random directions and saved the path in a vector. It "hits a wall" if the tile after wich its facing is a path. When stuck, go back one step reading its memory.
 /// Iteration limit to zero to disable the limit.
+/// Every random roll is drawn from a single RNG seeded with `config.seed`, so the same config gives back the same labyrinth.
 pub fn random_memory_based(
-    grid_size: usize, 
-    iteration_limit: usize, 
+    config: &basics::GeneratorConfig,
     labyrinth_ui_features: &HashMap<grid::TileFeatures, 
     &'static str>
-) -> grid::Grid {
-    if ui::DEBUG_LOGGING == ui::DebugLogging::Minimal || ui::DEBUG_LOGGING == ui::DebugLogging::All {println!("## Generating labyrinth.");}
-    // Init & generator settings
-    let grid_default_state: bool = basics::DEFAULT_STATE;
-    let stuck_reaction: basics::StuckReaction = basics::DEFAULT_STUCK;
-    let islets: basics::Islet = basics::DEFAULT_ISLET;
-    let subordination: basics::Unsubordination = basics::DEFAULT_UNSUBORDINATION;
-
-    let grid_default_features: Vec<grid::TileFeatures> = Vec::new();
-    let grid_kind: grid::GridKind = grid::GridKind::Squares;
-
+) -> Labyrinth {
+    if ui::DEBUG_LOGGING == ui::DebugLogging::Minimal || ui::DEBUG_LOGGING == ui::DebugLogging::All {println!("## Generating labyrinth (seed {}).", config.seed);}
     // Init labyrinth's grid.
-    let mut grid_labyrinth: grid::Grid = grid::Grid::new(grid_kind, grid_size, grid_default_state);  
-    let mut counter: usize = 0; 
+    let mut grid_labyrinth: grid::Grid = grid_initialize(config, config.default_state);  
 
-    // Start at the middle of the grid
-    let mut generator_position: basics::Position = basics::Position { x: grid_labyrinth.size.x as i32 / 2, y: grid_labyrinth.size.y as i32 / 2 };
+    // Start at the middle of the grid, by default
+    let start: basics::Position = start_tile(config);
     grid_labyrinth.update_tile(
-        generator_position.x, 
-        generator_position.y, 
-        !grid_default_state, 
+        start.x, 
+        start.y, 
+        !config.default_state, 
         vec![grid::TileFeatures::Named("Entrance")]
     );
+    let crossings: Vec<basics::Position> = random_memory_walk(&mut grid_labyrinth, start, config, labyrinth_ui_features, None);
+
+    let mut labyrinth: Labyrinth = Labyrinth::new(grid_labyrinth, config, start);
+    labyrinth.tags = crossings.into_iter().map(|tile| (tile, grid::TileFeatures::Named("Crossing"))).collect();
+    labyrinth
+}
+
+/// Tile of the corridor a weave can cross under, the walker being on `tile` after one step of `offset`.
+/// The corridor is straight and perpendicular, and not a crossing already; the tiles around the way in and out are walls.
+fn weave_crossing(
+    grid_labyrinth: &grid::Grid,
+    config: &basics::GeneratorConfig,
+    tile: basics::Position,
+    offset: basics::Position,
+    crossings: &[basics::Position]
+) -> Option<basics::Position> {
+    let side: basics::Position = basics::Position { x: offset.y.abs(), y: offset.x.abs() };
+    let at = |base: basics::Position, forward: i32, sideways: i32| basics::Position {
+        x: base.x + offset.x * forward + side.x * sideways,
+        y: base.y + offset.y * forward + side.y * sideways,
+    };
+    let is = |tile: basics::Position, state: bool| match state_tile(grid_labyrinth, config, tile.x, tile.y) {
+        grid::TileState::On => state,
+        grid::TileState::Off => !state,
+        grid::TileState::Void => false,
+    };
+    let crossing: basics::Position = at(tile, 1, 0);
+    let corridor: bool = [-1, 0, 1].iter().all(|sideways| is(at(tile, 1, *sideways), true));
+    let way_in: bool = [-1, 0, 1].iter().all(|sideways| is(at(tile, 0, *sideways), false));
+    let way_out: bool = [2, 3].iter().all(|forward| [-1, 0, 1].iter().all(|sideways| is(at(tile, *forward, *sideways), false)));
+    (corridor && way_in && way_out && !crossings.contains(&crossing)).then_some(crossing)
+}
+
+/// Extra move of the walker, tried before each step: from the tile the walker is on, carve and give the tile it carries on from, or `None` to step as usual.
+pub type WalkHook<'a> = &'a mut dyn FnMut(&mut grid::Grid, basics::Position, &mut StdRng) -> Option<basics::Position>;
+
+/// # Walker of `random_memory_based`, on an existing grid.
+/// Start from the tile `start`, expected to be carved already, and only carve where the field of view sees no path: existing paths of the grid are walked around.
+/// With `config.weave`, facing a straight corridor, it may tunnel under it instead: gives the crossing tiles, passed over and under.
+/// With `config.bias`, directions are drawn after their weights, and the last one may be kept to go straight.
+/// With a `hook`, the walker can also jump elsewhere, like the stairs of `levels::levels_memory_based`.
+pub fn random_memory_walk(
+    grid_labyrinth: &mut grid::Grid,
+    start: basics::Position,
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>,
+    mut hook: Option<WalkHook>
+) -> Vec<basics::Position> {
+    // Init & generator settings
+    let iteration_limit: usize = config.iteration_limit;
+    let grid_default_state: bool = config.default_state;
+    let stuck_reaction: basics::StuckReaction = config.stuck_reaction;
+    let islets: basics::Islet = config.islet;
+    let subordination: basics::Unsubordination = config.unsubordination;
+    let bias: basics::DirectionBias = config.bias;
+
+    let grid_default_features: Vec<grid::TileFeatures> = Vec::new();
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let mut counter: usize = 0; 
+
+    let mut generator_position: basics::Position = start;
     let mut generator_path: Vec<basics::Position> = vec![generator_position];
     let mut generator_index: usize = 0;
+    let mut crossings: Vec<basics::Position> = Vec::new();
+    // Direction of the last step, forgotten when rewinding.
+    let mut generator_direction: Option<basics::OrdinalDirections> = None;
     if ui::DEBUG_LOGGING == ui::DebugLogging::Minimal || ui::DEBUG_LOGGING == ui::DebugLogging::All {println!("- Vars initalized.\n- Starting main loop.");}
     
     // Generator, end when the generator has backed up totaly.
@@ -46,6 +550,14 @@ pub fn random_memory_based(
         let mut available_directions: Vec<basics::OrdinalDirections> = vec![basics::OrdinalDirections::North, basics::OrdinalDirections::East, basics::OrdinalDirections::South, basics::OrdinalDirections::West];
 
         if ui::DEBUG_LOGGING == ui::DebugLogging::All {print!(" - Iter {}; ", counter);}
+        // Extra move, in place of the step.
+        if let Some(hook) = hook.as_mut()
+            && let Some(target) = hook(grid_labyrinth, generator_position, &mut generator_rng)
+        {
+            generator_position = target;
+            generator_direction = None;
+            good_path = true;
+        }
         // Expect to find a good path; if not, if all direction are blocked
         while !good_path && available_directions.len() > 0 {
             let offset_x: i8;
@@ -54,7 +566,17 @@ pub fn random_memory_based(
             let arc_direction_y: i8;
             
 
-            let direction: &basics::OrdinalDirections = available_directions.choose(&mut rand::rng()).expect("(!) - Something went wrong with the random choice");
+            let direction: basics::OrdinalDirections = match generator_direction {
+                Some(last) if bias.straight > 0f32
+                    && available_directions.contains(&last)
+                    && generator_rng.random::<f32>() < bias.straight => last,
+                _ if bias.is_uniform() => *available_directions.choose(&mut generator_rng).expect("(!) - Something went wrong with the random choice"),
+                _ => match available_directions.choose_weighted(&mut generator_rng, |direction| bias.weight(*direction)) {
+                    Ok(direction) => *direction,
+                    // Only zero weights left: uniform among them.
+                    Err(_) => *available_directions.choose(&mut generator_rng).expect("(!) - Something went wrong with the random choice"),
+                },
+            };
             
             match direction {
                 basics::OrdinalDirections::North => {
@@ -88,7 +610,7 @@ pub fn random_memory_based(
 
             // Move like it is good.
             generator_position = basics::Position { x: generator_position.x + offset_x as i32, y: generator_position.y + offset_y as i32};
-            let generator_position_state: grid::TileState = grid_labyrinth.state_tile(generator_position.x, generator_position.y);
+            let generator_position_state: grid::TileState = state_tile(grid_labyrinth, config, generator_position.x, generator_position.y);
             
             // Check neighbours, with the "field of view", according to the offset.
             let mut generator_neighbours_pass: bool = true;
@@ -100,7 +622,7 @@ pub fn random_memory_based(
                 generator_arc = basics::NEIGHBOURS_ARC_X_1;
             }
             for neighbour in generator_arc {
-                let tile_state: grid::TileState = grid_labyrinth.state_tile(
+                let tile_state: grid::TileState = state_tile(grid_labyrinth, config, 
                     generator_position.x + arc_direction_x as i32 * neighbour.x, 
                     generator_position.y + arc_direction_y as i32 * neighbour.y
                 );
@@ -122,7 +644,7 @@ pub fn random_memory_based(
 
             // Other probable checks.
             if let basics::Islet::Yes(p) = islets
-                && let grid::TileState::On = grid_labyrinth.state_tile(
+                && let grid::TileState::On = state_tile(grid_labyrinth, config, 
                     generator_position.x + offset_x as i32,
                     generator_position.y + offset_y as i32
                 )
@@ -130,7 +652,7 @@ pub fn random_memory_based(
                 && !generator_on_border 
             {
                 // Apply chance of islet only if connect to another path, so reverting the check
-                if rand::random::<f32>() <= p {
+                if generator_rng.random::<f32>() <= p {
                     let generator_arc_islet: [basics::Position; 2];
                     let mut generator_islet_pass: bool = true;
                     if offset_x == 0 {
@@ -139,7 +661,7 @@ pub fn random_memory_based(
                         generator_arc_islet = basics::NEIGHBOURS_ARC_XI_1;
                     }
                     for neighbour in generator_arc_islet {
-                        let tile_state: grid::TileState = grid_labyrinth.state_tile(
+                        let tile_state: grid::TileState = state_tile(grid_labyrinth, config, 
                             generator_position.x + arc_direction_x as i32 * neighbour.x, 
                             generator_position.y + arc_direction_y as i32 * neighbour.y
                         );
@@ -171,13 +693,25 @@ pub fn random_memory_based(
                 && !generator_on_border 
             {
                 // Unsubordination, ignoring all.
-                if rand::random::<f32>() <= p {
+                if generator_rng.random::<f32>() <= p {
                     generator_neighbours_pass = true;
                     ui::dp(format!("Unsubordination! {};", p), ui::DebugLogging::All); 
                 }
             }
 
 
+            // Weave, where no islet joined the path in front.
+            let mut generator_crossing: Option<basics::Position> = None;
+            if config.weave > 0f32
+                && !generator_neighbours_pass
+                && !generator_on_border
+                && let Some(crossing) = weave_crossing(grid_labyrinth, config, generator_position, basics::Position { x: offset_x as i32, y: offset_y as i32 }, &crossings)
+                && generator_rng.random::<f32>() < config.weave
+            {
+                generator_crossing = Some(crossing);
+                ui::dp(format!("Weave under x={}, y={}; ", crossing.x, crossing.y), ui::DebugLogging::All);
+            }
+
             if ui::DEBUG_LOGGING == ui::DebugLogging::All {print!("Gene pos: x={}, y={}; ", generator_position.x, generator_position.y);}
             
             if let grid::TileState::Off = generator_position_state 
@@ -186,13 +720,23 @@ pub fn random_memory_based(
             {
                 // No neighbours, or allowed to create an islet.
                 grid_labyrinth.update_tile(generator_position.x, generator_position.y, !grid_default_state, grid_default_features.clone());
+                generator_direction = Some(direction);
+                good_path = true;
+            } else if let Some(crossing) = generator_crossing {
+                // Tunnel: the tile before the corridor, the crossing, and the tile after it, where the walker carries on.
+                grid_labyrinth.update_tile(generator_position.x, generator_position.y, !grid_default_state, grid_default_features.clone());
+                grid_labyrinth.update_tile(crossing.x, crossing.y, !grid_default_state, vec![grid::TileFeatures::Named("Crossing")]);
+                crossings.push(crossing);
+                generator_position = basics::Position { x: crossing.x + offset_x as i32, y: crossing.y + offset_y as i32 };
+                grid_labyrinth.update_tile(generator_position.x, generator_position.y, !grid_default_state, grid_default_features.clone());
+                generator_direction = Some(direction);
                 good_path = true;
             } else {
                 // Nevermind, tile was not good, go back to the original tile.
                 generator_position = basics::Position { 
                     x: generator_position.x - offset_x as i32, 
                     y: generator_position.y - offset_y as i32};
-                available_directions.remove(available_directions.iter().position(|d| d == direction).expect("(!) - Can't find direction."));
+                available_directions.remove(available_directions.iter().position(|d| *d == direction).expect("(!) - Can't find direction."));
             }
         }
 
@@ -202,11 +746,12 @@ pub fn random_memory_based(
             if ui::DEBUG_LOGGING == ui::DebugLogging::All {print!("Good path. ");}
         } else {
             generator_path.remove(generator_index);
+            generator_direction = None;
             match stuck_reaction {
                 // Method branch-random
                 basics::StuckReaction::RandomPosition => {
                     if generator_path.len() > 0 {
-                        generator_index = rng().random_range(0..generator_path.len());
+                        generator_index = generator_rng.random_range(0..generator_path.len());
                         if ui::DEBUG_LOGGING == ui::DebugLogging::All {print!("Stuck: rewinding (RP). ");}
                         generator_position = generator_path[generator_index];
                     } else {
@@ -241,7 +786,161 @@ pub fn random_memory_based(
         }
         
     }
-
-    grid_labyrinth
+    crossings
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Paths of the labyrinth, row by row.
+    fn paths(labyrinth: &Labyrinth) -> Vec<bool> {
+        (0..labyrinth.height as i32)
+            .flat_map(|y| (0..labyrinth.width as i32).map(move |x| (x, y)))
+            .map(|(x, y)| matches!(labyrinth.state_tile(x, y), grid::TileState::On))
+            .collect()
+    }
+
+    /// Count of the path tiles, and of those reached from the entrance.
+    pub(super) fn paths_reached(labyrinth: &Labyrinth) -> (usize, usize) {
+        (paths(labyrinth).iter().filter(|path| **path).count(), reached(labyrinth).iter().filter(|path| **path).count())
+    }
+
+    /// Path tiles reached from the entrance, stepping North, East, South and West; row by row.
+    pub(super) fn reached(labyrinth: &Labyrinth) -> Vec<bool> {
+        let width: usize = labyrinth.width;
+        let on: Vec<bool> = paths(labyrinth);
+        let mut reached: Vec<bool> = vec![false; on.len()];
+        let mut stack: Vec<basics::Position> = Vec::new();
+        let start: usize = labyrinth.entrance.y as usize * width + labyrinth.entrance.x as usize;
+        if on[start] {
+            reached[start] = true;
+            stack.push(labyrinth.entrance);
+        }
+        while let Some(tile) = stack.pop() {
+            for direction in basics::OrdinalDirections::ALL {
+                let offset: basics::Position = direction.offset();
+                let next: basics::Position = basics::Position { x: tile.x + offset.x, y: tile.y + offset.y };
+                if next.x < 0 || next.y < 0 || next.x as usize >= width || next.y as usize >= labyrinth.height {
+                    continue;
+                }
+                let index: usize = next.y as usize * width + next.x as usize;
+                if on[index] && !reached[index] {
+                    reached[index] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Rectangular config of the tests, 10 by 6 cells.
+    pub(super) fn config_test() -> basics::GeneratorConfig {
+        basics::GeneratorConfig::new().width(21).height(13)
+    }
+
+    /// Assert the generator gives perfect mazes with the config, on a few seeds: every cell of the lattice carved, 
+    /// no post, one passage less than the cells, all reached from the entrance.
+    pub(super) fn assert_perfect(generator: &dyn MazeGenerator, config_base: basics::GeneratorConfig) {
+        for seed in 0..8u64 {
+            let config: basics::GeneratorConfig = config_base.clone().seed(seed);
+            let labyrinth: Labyrinth = generator.generate(&config, &HashMap::new());
+            let lattice: Lattice = Lattice::of_config(&config);
+            let cells: Vec<basics::Position> = (0..lattice.len())
+                .map(|index| lattice.cell(index))
+                .filter(|cell| lattice.contains(*cell))
+                .collect();
+            for cell in &cells {
+                let tile: basics::Position = Lattice::tile(*cell);
+                assert!(matches!(labyrinth.state_tile(tile.x, tile.y), grid::TileState::On), "{} seed {}: cell {:?} not carved", generator.name(), seed, (cell.x, cell.y));
+            }
+            for y in (0..config.height as i32).step_by(2) {
+                for x in (0..config.width as i32).step_by(2) {
+                    assert!(!matches!(labyrinth.state_tile(x, y), grid::TileState::On), "{} seed {}: post x={}, y={} carved", generator.name(), seed, x, y);
+                }
+            }
+            let (paths, reached) = paths_reached(&labyrinth);
+            assert_eq!(paths, cells.len() * 2 - 1, "{} seed {}", generator.name(), seed);
+            assert_eq!(reached, paths, "{} seed {}", generator.name(), seed);
+        }
+    }
+
+    #[test]
+    fn perfect_maze_within_masks() {
+        let names: [&str; 11] = [
+            "backtracker", "prim", "kruskal", "wilson", "aldous_broder", "eller", 
+            "growing_tree", "hunt_and_kill", "binary_tree", "sidewinder", "recursive_division",
+        ];
+        for shape in [basics::MaskShape::Circle, basics::MaskShape::Heart, basics::MaskShape::Diamond] {
+            for name in names {
+                let Some(Algorithm::Tiles(generator)) = find(name) else {
+                    panic!("{} is not a tile generator", name);
+                };
+                assert_perfect(generator.as_ref(), config_test().mask(Some(basics::Mask::shape(shape, 21, 13))));
+            }
+        }
+    }
+
+    #[test]
+    fn random_memory_based_inside_mask() {
+        for seed in 0..8u64 {
+            let config: basics::GeneratorConfig = config_test().seed(seed).mask(Some(basics::Mask::shape(basics::MaskShape::Heart, 21, 13)));
+            let labyrinth: Labyrinth = random_memory_based(&config, &HashMap::new());
+            for y in 0..config.height as i32 {
+                for x in 0..config.width as i32 {
+                    if !config.inside(basics::Position { x, y }) {
+                        assert!(!matches!(labyrinth.grid.state_tile(x, y), grid::TileState::On), "seed {}: x={}, y={} outside", seed, x, y);
+                    }
+                }
+            }
+            let (paths, reached) = paths_reached(&labyrinth);
+            assert_eq!(reached, paths, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn weave_crossings_go_through() {
+        let mut crossings_count: usize = 0usize;
+        for seed in 0..8u64 {
+            let config: basics::GeneratorConfig = config_test().seed(seed).weave(1f32);
+            let labyrinth: Labyrinth = random_memory_based(&config, &HashMap::new());
+            for (crossing, feature) in &labyrinth.tags {
+                assert!(*feature == grid::TileFeatures::Named("Crossing"));
+                assert!(labyrinth.tags.iter().filter(|(tile, _)| tile == crossing).count() == 1, "seed {}: crossing tagged twice", seed);
+                // Both corridors, the one over and the one under, go on through the crossing.
+                for direction in basics::OrdinalDirections::ALL {
+                    let offset: basics::Position = direction.offset();
+                    assert!(
+                        matches!(labyrinth.state_tile(crossing.x + offset.x, crossing.y + offset.y), grid::TileState::On),
+                        "seed {}: crossing x={}, y={} closed", seed, crossing.x, crossing.y
+                    );
+                }
+                crossings_count += 1usize;
+            }
+            let (paths, reached) = paths_reached(&labyrinth);
+            assert_eq!(reached, paths, "seed {}", seed);
+        }
+        assert!(crossings_count > 0);
+    }
+
+    #[test]
+    fn random_memory_based_same_seed() {
+        let config: basics::GeneratorConfig = basics::GeneratorConfig::new().width(21).height(15).seed(11);
+        let first: Labyrinth = random_memory_based(&config, &HashMap::new());
+        let again: Labyrinth = random_memory_based(&config, &HashMap::new());
+        assert_eq!(paths(&first), paths(&again));
+        assert!(paths(&first).iter().any(|path| *path));
+        let other: Labyrinth = random_memory_based(&config.clone().seed(12), &HashMap::new());
+        assert_ne!(paths(&first), paths(&other));
+    }
+
+    #[test]
+    fn registry_names_unique() {
+        let names: Vec<&'static str> = registry().iter().map(|algorithm| algorithm.name()).collect();
+        for name in &names {
+            assert_eq!(names.iter().filter(|other| *other == name).count(), 1, "{}", name);
+            assert!(find(name).is_some());
+        }
+        assert!(find("nothing").is_none());
+    }
+}
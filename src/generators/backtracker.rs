@@ -0,0 +1,89 @@
+// LABYRINTH
+// Generators - Recursive backtracker
+
+use super::super::{HashMap, seq::IndexedRandom, SeedableRng, StdRng};
+use super::super::{basics, ui, grid};
+use super::{MazeGenerator, Lattice, Labyrinth};
+
+/// # Labyrinth generator; depth-first backtracker, with an explicit stack.
+/// Carve toward a random unvisited neighbour cell, and pop the stack when none is left. Gives a perfect maze with long corridors.
+/// Iteration limit to zero to disable the limit.
+pub fn recursive_backtracker(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, backtracker (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let (mut grid_labyrinth, lattice, entrance) = super::lattice_initialize(config);
+    if lattice.is_empty() {
+        return Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance));
+    }
+
+    let mut visited: Vec<bool> = vec![false; lattice.len()];
+    visited[lattice.index(entrance)] = true;
+    let mut generator_stack: Vec<basics::Position> = vec![entrance];
+    let mut counter: usize = 0;
+
+    while let Some(&cell) = generator_stack.last() 
+        && (config.iteration_limit < 1 || counter < config.iteration_limit) 
+    {
+        counter += 1usize;
+        let unvisited: Vec<basics::Position> = lattice
+            .neighbours(cell)
+            .into_iter()
+            .map(|(_, neighbour)| neighbour)
+            .filter(|neighbour| !visited[lattice.index(*neighbour)])
+            .collect();
+
+        match unvisited.choose(&mut generator_rng) {
+            Some(&next) => {
+                super::carve_passage(&mut grid_labyrinth, config, cell, next);
+                visited[lattice.index(next)] = true;
+                generator_stack.push(next);
+            },
+            None => {
+                generator_stack.pop();
+            },
+        }
+
+        if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+            grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+        }
+    }
+    if config.iteration_limit >= 1 && counter >= config.iteration_limit {
+        ui::dp(format!("- Iteration limit reached ({}).\n", config.iteration_limit), ui::DebugLogging::Minimal);
+    }
+
+    Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance))
+}
+
+/// `MazeGenerator` of `recursive_backtracker`.
+pub struct Backtracker;
+
+impl MazeGenerator for Backtracker {
+    fn name(&self) -> &'static str {
+        "backtracker"
+    }
+
+    fn description(&self) -> &'static str {
+        "Depth-first recursive backtracker; perfect maze, long corridors."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        recursive_backtracker(config, labyrinth_ui_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfect_maze() {
+        super::super::tests::assert_perfect(&Backtracker, super::super::tests::config_test());
+    }
+}
@@ -0,0 +1,169 @@
+// LABYRINTH
+// Generators - Binary tree and sidewinder, row-local and biased
+
+use super::super::{HashMap, Rng, SeedableRng, StdRng};
+use super::super::{basics, ui, grid};
+use super::{MazeGenerator, Lattice, Labyrinth};
+
+/// Cell next to `cell` in the direction.
+fn step(cell: basics::Position, direction: basics::OrdinalDirections) -> basics::Position {
+    let offset: basics::Position = direction.offset();
+    basics::Position { x: cell.x + offset.x, y: cell.y + offset.y }
+}
+
+/// Carve the wall between two adjacent cells only; the cells are carved beforehand.
+fn carve_wall(grid_labyrinth: &mut grid::Grid, config: &basics::GeneratorConfig, from: basics::Position, to: basics::Position) {
+    let wall: basics::Position = Lattice::wall(from, to);
+    grid_labyrinth.update_tile(wall.x, wall.y, !config.default_state, Vec::new());
+}
+
+/// Carve all the cells of the lattice except the entrance, already carved and tagged.
+fn carve_all_cells(grid_labyrinth: &mut grid::Grid, lattice: &Lattice, config: &basics::GeneratorConfig, entrance: basics::Position) {
+    for index in 0..lattice.len() {
+        let cell: basics::Position = lattice.cell(index);
+        if cell != entrance && lattice.contains(cell) {
+            super::carve_cell(grid_labyrinth, config, cell, Vec::new());
+        }
+    }
+}
+
+/// # Labyrinth generator; binary tree.
+/// Each cell opens either North or East, at random. No memory at all; strongly biased, with two straight corridors along the North and East borders.
+pub fn binary_tree(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, binary tree (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let (mut grid_labyrinth, lattice, entrance) = super::lattice_initialize(config);
+    carve_all_cells(&mut grid_labyrinth, &lattice, config, entrance);
+
+    for index in 0..lattice.len() {
+        let cell: basics::Position = lattice.cell(index);
+        if !lattice.contains(cell) {
+            continue;
+        }
+        let north: basics::Position = step(cell, basics::OrdinalDirections::North);
+        let east: basics::Position = step(cell, basics::OrdinalDirections::East);
+        let next: basics::Position = match (lattice.contains(north), lattice.contains(east)) {
+            (true, true) => if generator_rng.random_bool(0.5) { north } else { east },
+            (true, false) => north,
+            (false, true) => east,
+            (false, false) => continue,
+        };
+        carve_wall(&mut grid_labyrinth, config, cell, next);
+    }
+    // Cells with neither North nor East in the silhouette are roots of their own trees.
+    super::lattice_mask_fit(&mut grid_labyrinth, config, &lattice, entrance, &mut generator_rng);
+    if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+        grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+    }
+
+    Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance))
+}
+
+/// # Labyrinth generator; sidewinder.
+/// Row by row, grow runs of cells toward East; closing a run opens one of its cells to the North. 
+/// Only the current run is kept; biased, with a straight corridor along the North border.
+pub fn sidewinder(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, sidewinder (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let (mut grid_labyrinth, lattice, entrance) = super::lattice_initialize(config);
+    carve_all_cells(&mut grid_labyrinth, &lattice, config, entrance);
+
+    let mut run: Vec<basics::Position> = Vec::new();
+    for y in 0..lattice.height {
+        for x in 0..lattice.width {
+            let cell: basics::Position = basics::Position { x: x as i32, y: y as i32 };
+            if !lattice.contains(cell) {
+                continue;
+            }
+            run.push(cell);
+            // Cells of the run that can open North: none on the top row, nor under the edge of a mask.
+            let run_north: Vec<basics::Position> = run
+                .iter()
+                .copied()
+                .filter(|member| lattice.contains(step(*member, basics::OrdinalDirections::North)))
+                .collect();
+            let east: basics::Position = step(cell, basics::OrdinalDirections::East);
+            let run_close: bool = !lattice.contains(east) || (!run_north.is_empty() && generator_rng.random_bool(0.5));
+
+            if run_close {
+                if !run_north.is_empty() {
+                    let from: basics::Position = run_north[generator_rng.random_range(0..run_north.len())];
+                    carve_wall(&mut grid_labyrinth, config, from, step(from, basics::OrdinalDirections::North));
+                }
+                run.clear();
+            } else {
+                carve_wall(&mut grid_labyrinth, config, cell, east);
+            }
+        }
+    }
+    // Runs with no cell under the silhouette to open North are left apart.
+    super::lattice_mask_fit(&mut grid_labyrinth, config, &lattice, entrance, &mut generator_rng);
+    if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+        grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+    }
+
+    Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance))
+}
+
+/// `MazeGenerator` of `binary_tree`.
+pub struct BinaryTree;
+
+impl MazeGenerator for BinaryTree {
+    fn name(&self) -> &'static str {
+        "binary_tree"
+    }
+
+    fn description(&self) -> &'static str {
+        "Binary tree; each cell opens North or East; cheap, strong diagonal bias."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        binary_tree(config, labyrinth_ui_features)
+    }
+}
+
+/// `MazeGenerator` of `sidewinder`.
+pub struct Sidewinder;
+
+impl MazeGenerator for Sidewinder {
+    fn name(&self) -> &'static str {
+        "sidewinder"
+    }
+
+    fn description(&self) -> &'static str {
+        "Sidewinder; runs East closed by a North opening; cheap, vertical bias."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        sidewinder(config, labyrinth_ui_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_tree_perfect_maze() {
+        super::super::tests::assert_perfect(&BinaryTree, super::super::tests::config_test());
+    }
+
+    #[test]
+    fn sidewinder_perfect_maze() {
+        super::super::tests::assert_perfect(&Sidewinder, super::super::tests::config_test());
+    }
+}
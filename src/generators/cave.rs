@@ -0,0 +1,174 @@
+// LABYRINTH
+// Generators - Cellular automaton caves
+
+use std::collections::VecDeque;
+use super::super::{HashMap, Rng, SeedableRng, StdRng};
+use super::super::{basics, ui, grid};
+use super::{MazeGenerator, Labyrinth};
+
+/// Count of walls among the 8 neighbours of the tile; outside the field counts as wall.
+fn walls_around(walls: &[bool], width: usize, height: usize, x: usize, y: usize) -> usize {
+    let mut count: usize = 0;
+    for offset_y in -1i32..=1 {
+        for offset_x in -1i32..=1 {
+            if offset_x == 0 && offset_y == 0 {
+                continue;
+            }
+            let neighbour_x: i32 = x as i32 + offset_x;
+            let neighbour_y: i32 = y as i32 + offset_y;
+            if neighbour_x < 0 
+                || neighbour_y < 0 
+                || neighbour_x >= width as i32 
+                || neighbour_y >= height as i32 
+                || walls[neighbour_y as usize * width + neighbour_x as usize] 
+            {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+/// Label each open tile with its region (4-connected), `usize::MAX` for walls. Gives the labels and the size of each region.
+fn regions(walls: &[bool], width: usize, height: usize) -> (Vec<usize>, Vec<usize>) {
+    let mut labels: Vec<usize> = vec![usize::MAX; walls.len()];
+    let mut sizes: Vec<usize> = Vec::new();
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    for start in 0..walls.len() {
+        if walls[start] || labels[start] != usize::MAX {
+            continue;
+        }
+        let label: usize = sizes.len();
+        let mut size: usize = 0;
+        labels[start] = label;
+        queue.push_back(start);
+        while let Some(index) = queue.pop_front() {
+            size += 1;
+            let (x, y) = (index % width, index / width);
+            let mut neighbours: Vec<usize> = Vec::with_capacity(4);
+            if x > 0 { neighbours.push(index - 1); }
+            if x + 1 < width { neighbours.push(index + 1); }
+            if y > 0 { neighbours.push(index - width); }
+            if y + 1 < height { neighbours.push(index + width); }
+            for neighbour in neighbours {
+                if !walls[neighbour] && labels[neighbour] == usize::MAX {
+                    labels[neighbour] = label;
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        sizes.push(size);
+    }
+    (labels, sizes)
+}
+
+/// # Labyrinth generator; cellular automaton caves.
+/// Fill the field with random walls (`config.cave_fill`), apply `config.cave_rule` for `config.cave_steps` steps, then keep only the largest open region.
+/// The entrance is the tile of the region the nearest to the start. Gives organic caverns instead of corridors.
+pub fn cave(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, cave (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let mut grid_labyrinth: grid::Grid = super::grid_initialize(config, config.default_state);
+    let width: usize = config.width;
+    let height: usize = config.height;
+
+    // Outside of the mask stays solid.
+    let solid: Vec<bool> = (0..width * height)
+        .map(|index| !config.inside(basics::Position { x: (index % width) as i32, y: (index / width) as i32 }))
+        .collect();
+    // Noise, with a solid border.
+    let mut walls: Vec<bool> = (0..width * height)
+        .map(|index| {
+            let (x, y) = (index % width, index / width);
+            x == 0 || y == 0 || x + 1 == width || y + 1 == height || solid[index] || generator_rng.random::<f32>() < config.cave_fill
+        })
+        .collect();
+
+    // Birth and survival steps.
+    for step in 0..config.cave_steps {
+        walls = (0..width * height)
+            .map(|index| {
+                let (x, y) = (index % width, index / width);
+                let count: usize = walls_around(&walls, width, height, x, y);
+                solid[index] || if walls[index] { config.cave_rule.survival[count] } else { config.cave_rule.birth[count] }
+            })
+            .collect();
+        ui::dp(format!("- Step {} done.\n", step), ui::DebugLogging::All);
+    }
+
+    // Keep the largest region.
+    let (labels, sizes) = regions(&walls, width, height);
+    let Some(region_largest) = (0..sizes.len()).max_by_key(|label| sizes[*label]) else {
+        ui::dp(String::from("- No open region left.\n"), ui::DebugLogging::Minimal);
+        let start: basics::Position = super::start_tile(config);
+        return Labyrinth::new(grid_labyrinth, config, start);
+    };
+    let start: basics::Position = super::start_tile(config);
+    let mut entrance: basics::Position = start;
+    let mut entrance_distance: i32 = i32::MAX;
+    for (index, label) in labels.iter().enumerate() {
+        if *label != region_largest {
+            continue;
+        }
+        let tile: basics::Position = basics::Position { x: (index % width) as i32, y: (index / width) as i32 };
+        let distance: i32 = (tile.x - start.x).abs() + (tile.y - start.y).abs();
+        if distance < entrance_distance {
+            entrance = tile;
+            entrance_distance = distance;
+        }
+        grid_labyrinth.update_tile(tile.x, tile.y, !config.default_state, Vec::new());
+    }
+    grid_labyrinth.update_tile(entrance.x, entrance.y, !config.default_state, vec![grid::TileFeatures::Named("Entrance")]);
+    ui::dp(format!("- Largest region: {} tiles, of {} regions.\n", sizes[region_largest], sizes.len()), ui::DebugLogging::Minimal);
+
+    if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+        grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+    }
+
+    Labyrinth::new(grid_labyrinth, config, entrance)
+}
+
+/// `MazeGenerator` of `cave`.
+pub struct Cave;
+
+impl MazeGenerator for Cave {
+    fn name(&self) -> &'static str {
+        "cave"
+    }
+
+    fn description(&self) -> &'static str {
+        "Cellular automaton caves, B678/S345678 by default; organic caverns, one connected region."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        cave(config, labyrinth_ui_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_region_inside_border() {
+        for seed in 0..8u64 {
+            let config: basics::GeneratorConfig = super::super::tests::config_test().width(31).height(21).seed(seed);
+            let labyrinth: Labyrinth = cave(&config, &HashMap::new());
+            let (paths, reached) = super::super::tests::paths_reached(&labyrinth);
+            assert!(paths > 0, "seed {}", seed);
+            assert_eq!(reached, paths, "seed {}", seed);
+            for x in 0..config.width as i32 {
+                for y in [0, config.height as i32 - 1] {
+                    assert!(!matches!(labyrinth.state_tile(x, y), grid::TileState::On), "seed {}", seed);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+// LABYRINTH
+// Generators - Recursive division, adding walls
+
+use super::super::{HashMap, Rng, SeedableRng, StdRng};
+use super::super::{basics, ui, grid};
+use super::{MazeGenerator, Lattice, Labyrinth};
+
+/// Rectangle of cells still to divide.
+#[derive(Clone, Copy)]
+struct Chamber {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+/// # Labyrinth generator; recursive division, with an explicit stack.
+/// Start from an open field, and split each chamber in two with a wall having a single gap, until chambers are one cell wide.
+/// Chambers up to `config.room_size` cells on both sides are left open. Gives long straight walls.
+/// Iteration limit (walls added) to zero to disable the limit.
+/// Walls are added, never carved: with a mask, the whole lattice is divided, then fitted to the silhouette by `lattice_mask_fit`.
+pub fn recursive_division(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, recursive division (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let wall_state: bool = config.default_state;
+
+    // Open field, inside walls around the lattice; the whole grid beyond is walled too.
+    let mut grid_labyrinth: grid::Grid = super::grid_initialize(config, !wall_state);
+    let lattice: Lattice = Lattice::of_size(config.width, config.height);
+    let tiles_max: basics::Position = basics::Position { x: grid_labyrinth.size.x as i32, y: grid_labyrinth.size.y as i32 };
+    let lattice_max: basics::Position = basics::Position { x: lattice.width as i32 * 2, y: lattice.height as i32 * 2 };
+    for x in 0..=tiles_max.x {
+        for y in 0..=tiles_max.y {
+            if x == 0 || y == 0 || x >= lattice_max.x || y >= lattice_max.y {
+                grid_labyrinth.update_tile(x, y, wall_state, Vec::new());
+            }
+        }
+    }
+
+    let mut chambers: Vec<Chamber> = vec![Chamber { x: 0, y: 0, width: lattice.width, height: lattice.height }];
+    let mut counter: usize = 0;
+    while let Some(chamber) = chambers.pop() 
+        && (config.iteration_limit < 1 || counter < config.iteration_limit) 
+    {
+        // One cell wide chambers are corridors, already connected.
+        if chamber.width < 2 
+            || chamber.height < 2 
+            || chamber.width <= config.room_size && chamber.height <= config.room_size
+        {
+            continue;
+        }
+        counter += 1usize;
+
+        let horizontal: bool = if chamber.width == chamber.height {
+            generator_rng.random_bool(0.5)
+        } else {
+            chamber.height > chamber.width
+        };
+
+        if horizontal {
+            // Wall above the row `cut`, gap at the column `gap`.
+            let cut: usize = chamber.y + generator_rng.random_range(0..chamber.height - 1);
+            let gap: usize = chamber.x + generator_rng.random_range(0..chamber.width);
+            let wall_y: i32 = cut as i32 * 2 + 2;
+            for x in (chamber.x * 2 + 1)..(chamber.x + chamber.width) * 2 {
+                if x != gap * 2 + 1 {
+                    grid_labyrinth.update_tile(x as i32, wall_y, wall_state, Vec::new());
+                }
+            }
+            chambers.push(Chamber { y: chamber.y, height: cut - chamber.y + 1, ..chamber });
+            chambers.push(Chamber { y: cut + 1, height: chamber.y + chamber.height - cut - 1, ..chamber });
+        } else {
+            // Wall East of the column `cut`, gap at the row `gap`.
+            let cut: usize = chamber.x + generator_rng.random_range(0..chamber.width - 1);
+            let gap: usize = chamber.y + generator_rng.random_range(0..chamber.height);
+            let wall_x: i32 = cut as i32 * 2 + 2;
+            for y in (chamber.y * 2 + 1)..(chamber.y + chamber.height) * 2 {
+                if y != gap * 2 + 1 {
+                    grid_labyrinth.update_tile(wall_x, y as i32, wall_state, Vec::new());
+                }
+            }
+            chambers.push(Chamber { x: chamber.x, width: cut - chamber.x + 1, ..chamber });
+            chambers.push(Chamber { x: cut + 1, width: chamber.x + chamber.width - cut - 1, ..chamber });
+        }
+
+        if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+            grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+        }
+    }
+    if config.iteration_limit >= 1 && counter >= config.iteration_limit {
+        ui::dp(format!("- Iteration limit reached ({}).\n", config.iteration_limit), ui::DebugLogging::Minimal);
+    }
+
+    // Within the mask, from now on.
+    let lattice: Lattice = Lattice::of_config(config);
+    let entrance: basics::Position = lattice.cell_at(super::start_tile(config));
+    if !lattice.is_empty() {
+        super::carve_cell(&mut grid_labyrinth, config, entrance, vec![grid::TileFeatures::Named("Entrance")]);
+    }
+    super::lattice_mask_fit(&mut grid_labyrinth, config, &lattice, entrance, &mut generator_rng);
+
+    Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance))
+}
+
+/// `MazeGenerator` of `recursive_division`.
+pub struct RecursiveDivision;
+
+impl MazeGenerator for RecursiveDivision {
+    fn name(&self) -> &'static str {
+        "recursive_division"
+    }
+
+    fn description(&self) -> &'static str {
+        "Recursive division, adding walls to an open field; long straight walls, optional rooms."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        recursive_division(config, labyrinth_ui_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfect_maze() {
+        super::super::tests::assert_perfect(&RecursiveDivision, super::super::tests::config_test());
+    }
+
+    #[test]
+    fn rooms_connected() {
+        for seed in 0..8u64 {
+            let config: basics::GeneratorConfig = super::super::tests::config_test().room_size(3).seed(seed);
+            let (paths, reached) = super::super::tests::paths_reached(&recursive_division(&config, &HashMap::new()));
+            assert_eq!(reached, paths, "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+// LABYRINTH
+// Generators - Rooms and corridors dungeon
+
+use super::super::{HashMap, seq::SliceRandom, Rng, SeedableRng, StdRng};
+use super::super::{basics, ui, grid};
+use super::{MazeGenerator, Labyrinth};
+
+/// Rectangle of floor tiles.
+#[derive(Clone, Copy)]
+struct Room {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl Room {
+    /// If the rooms overlap or touch, keeping at least `gap` tiles between them.
+    fn near(&self, other: &Room, gap: i32) -> bool {
+        self.x - gap < other.x + other.width 
+            && other.x - gap < self.x + self.width 
+            && self.y - gap < other.y + other.height 
+            && other.y - gap < self.y + self.height
+    }
+
+    fn contains(&self, tile: basics::Position) -> bool {
+        tile.x >= self.x && tile.x < self.x + self.width && tile.y >= self.y && tile.y < self.y + self.height
+    }
+}
+
+/// Tiles next to the room, one wall away from a corridor: candidates for doors.
+fn door_candidates(
+    grid_labyrinth: &grid::Grid, 
+    room: &Room, 
+    rooms: &[Room]
+) -> Vec<basics::Position> {
+    let mut candidates: Vec<basics::Position> = Vec::new();
+    for tile_x in room.x..room.x + room.width {
+        for tile_y in room.y..room.y + room.height {
+            let tile: basics::Position = basics::Position { x: tile_x, y: tile_y };
+            for direction in basics::OrdinalDirections::ALL {
+                let offset: basics::Position = direction.offset();
+                let door: basics::Position = basics::Position { x: tile.x + offset.x, y: tile.y + offset.y };
+                let beyond: basics::Position = basics::Position { x: door.x + offset.x, y: door.y + offset.y };
+                if !room.contains(door)
+                    && let grid::TileState::Off = grid_labyrinth.state_tile(door.x, door.y)
+                    && let grid::TileState::On = grid_labyrinth.state_tile(beyond.x, beyond.y)
+                    && !rooms.iter().any(|other| other.contains(beyond))
+                {
+                    candidates.push(door);
+                }
+            }
+        }
+    }
+    candidates
+}
+
+/// # Labyrinth generator; rooms and corridors.
+/// Place non-overlapping rectangular rooms (`config.dungeon_rooms` attempts), fill the space left with the corridors of `random_memory_walk`, then open one or two doors per room.
+/// Rooms are tagged `Named("Room")`, doors `Named("Door")`.
+pub fn dungeon(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, dungeon (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    // Own stream for the rooms, so that the corridors walker keeps the seed as given.
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed.wrapping_add(1));
+    let mut grid_labyrinth: grid::Grid = super::grid_initialize(config, config.default_state);
+    let tiles_max: basics::Position = super::tiles_max(config);
+
+    // Rooms, with a margin of walls and room for corridors around them.
+    let room_side_max: i32 = (tiles_max.x.min(tiles_max.y) / 5).max(3);
+    let mut rooms: Vec<Room> = Vec::new();
+    let mut tags: Vec<(basics::Position, grid::TileFeatures)> = Vec::new();
+    for _ in 0..config.dungeon_rooms {
+        let width: i32 = generator_rng.random_range(3..=room_side_max);
+        let height: i32 = generator_rng.random_range(3..=room_side_max);
+        if tiles_max.x - width - 2 < 2 || tiles_max.y - height - 2 < 2 {
+            continue;
+        }
+        let room: Room = Room {
+            x: generator_rng.random_range(2..=tiles_max.x - width - 2),
+            y: generator_rng.random_range(2..=tiles_max.y - height - 2),
+            width,
+            height,
+        };
+        if rooms.iter().any(|other| room.near(other, 3)) {
+            continue;
+        }
+        // Walls around the room inside the mask too.
+        let room_inside: bool = (room.x - 1..=room.x + room.width)
+            .all(|tile_x| (room.y - 1..=room.y + room.height).all(|tile_y| config.inside(basics::Position { x: tile_x, y: tile_y })));
+        if !room_inside {
+            continue;
+        }
+        for tile_x in room.x..room.x + room.width {
+            for tile_y in room.y..room.y + room.height {
+                grid_labyrinth.update_tile(tile_x, tile_y, !config.default_state, vec![grid::TileFeatures::Named("Room")]);
+                tags.push((basics::Position { x: tile_x, y: tile_y }, grid::TileFeatures::Named("Room")));
+            }
+        }
+        rooms.push(room);
+    }
+    ui::dp(format!("- Rooms placed: {}.\n", rooms.len()), ui::DebugLogging::Minimal);
+
+    // Corridors, from the first tile far enough from every room, the nearest to the start.
+    let target: basics::Position = super::start_tile(config);
+    let mut start: Option<basics::Position> = None;
+    for tile_x in 1..tiles_max.x {
+        for tile_y in 1..tiles_max.y {
+            let tile: basics::Position = basics::Position { x: tile_x, y: tile_y };
+            let free: bool = !rooms.iter().any(|room| room.near(&Room { x: tile.x, y: tile.y, width: 1, height: 1 }, 2))
+                && config.mask.as_ref().is_none_or(|mask| mask.surrounds(tile));
+            let nearer: bool = match start {
+                Some(current) => (tile.x - target.x).abs() + (tile.y - target.y).abs() < (current.x - target.x).abs() + (current.y - target.y).abs(),
+                None => true,
+            };
+            if free && nearer {
+                start = Some(tile);
+            }
+        }
+    }
+    let Some(start) = start else {
+        ui::dp(String::from("- No space left for corridors.\n"), ui::DebugLogging::Minimal);
+        let mut labyrinth: Labyrinth = Labyrinth::new(grid_labyrinth, config, target);
+        labyrinth.tags = tags;
+        return labyrinth;
+    };
+    grid_labyrinth.update_tile(start.x, start.y, !config.default_state, vec![grid::TileFeatures::Named("Entrance")]);
+    let crossings: Vec<basics::Position> = super::random_memory_walk(&mut grid_labyrinth, start, config, labyrinth_ui_features, None);
+    tags.extend(crossings.into_iter().map(|tile| (tile, grid::TileFeatures::Named("Crossing"))));
+
+    // Doors
+    for room in &rooms {
+        let mut candidates: Vec<basics::Position> = door_candidates(&grid_labyrinth, room, &rooms);
+        candidates.shuffle(&mut generator_rng);
+        let doors: usize = generator_rng.random_range(1..=2).min(candidates.len());
+        if doors == 0 {
+            ui::dp(format!("- Room at x={}, y={} has no corridor next to it.\n", room.x, room.y), ui::DebugLogging::Minimal);
+        }
+        for door in candidates.into_iter().take(doors) {
+            grid_labyrinth.update_tile(door.x, door.y, !config.default_state, vec![grid::TileFeatures::Named("Door")]);
+            tags.push((door, grid::TileFeatures::Named("Door")));
+        }
+    }
+
+    if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+        grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+    }
+
+    let mut labyrinth: Labyrinth = Labyrinth::new(grid_labyrinth, config, start);
+    labyrinth.tags = tags;
+    labyrinth
+}
+
+/// `MazeGenerator` of `dungeon`.
+pub struct Dungeon;
+
+impl MazeGenerator for Dungeon {
+    fn name(&self) -> &'static str {
+        "dungeon"
+    }
+
+    fn description(&self) -> &'static str {
+        "Rooms and corridors; rectangular rooms joined by random_memory_based corridors and doors."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        dungeon(config, labyrinth_ui_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rooms_reached_through_doors() {
+        for seed in 0..8u64 {
+            let config: basics::GeneratorConfig = super::super::tests::config_test().width(41).height(31).seed(seed);
+            let labyrinth: Labyrinth = dungeon(&config, &HashMap::new());
+            let reached: Vec<bool> = super::super::tests::reached(&labyrinth);
+            let rooms: usize = labyrinth.tags.iter().filter(|(_, feature)| matches!(feature, grid::TileFeatures::Named("Room"))).count();
+            assert!(rooms > 0, "seed {}", seed);
+            for (tile, feature) in &labyrinth.tags {
+                if matches!(feature, grid::TileFeatures::Named("Room") | grid::TileFeatures::Named("Door")) {
+                    assert!(reached[tile.y as usize * config.width + tile.x as usize], "seed {}: x={}, y={}", seed, tile.x, tile.y);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// LABYRINTH
+// Generators - Eller's, row by row
+
+use std::collections::VecDeque;
+use super::super::{HashMap, Rng, SeedableRng, StdRng};
+use super::super::{basics, ui, grid};
+use super::{MazeGenerator, Lattice, Labyrinth};
+
+/// # Eller's algorithm, as an iterator of tile rows.
+/// Only the sets of the current row of cells are kept, so the height does not cost memory.
+/// Each item is a row of `2 * width + 1` tiles, `true` for a path; the whole labyrinth is `2 * height + 1` rows, borders included.
+pub struct EllerRows {
+    generator_rng: StdRng,
+    /// Width and height in cells.
+    width: usize,
+    height: usize,
+    /// Rows of cells already generated.
+    row: usize,
+    /// Set id of each cell of the current row, 0 if none yet.
+    sets: Vec<usize>,
+    set_next: usize,
+    /// Tile rows generated but not yet given.
+    pending: VecDeque<Vec<bool>>,
+    started: bool,
+}
+
+impl EllerRows {
+    pub fn new(width: usize, height: usize, seed: u64) -> Self {
+        EllerRows {
+            generator_rng: StdRng::seed_from_u64(seed),
+            width,
+            height,
+            row: 0,
+            sets: vec![0; width],
+            set_next: 1,
+            pending: VecDeque::new(),
+            started: false,
+        }
+    }
+
+    /// Width of the rows, in tiles.
+    pub fn tiles_width(&self) -> usize {
+        self.width * 2 + 1
+    }
+
+    /// Generate the next row of cells: its own tile row, and the walls below it.
+    fn next_cells_row(&mut self) {
+        let last: bool = self.row + 1 == self.height;
+        for set in self.sets.iter_mut() {
+            if *set == 0 {
+                *set = self.set_next;
+                self.set_next += 1;
+            }
+        }
+
+        // Join horizontally, always on the last row so that everything is connected.
+        let mut row_cells: Vec<bool> = vec![false; self.tiles_width()];
+        for x in 0..self.width {
+            row_cells[x * 2 + 1] = true;
+            if x + 1 < self.width 
+                && self.sets[x] != self.sets[x + 1] 
+                && (last || self.generator_rng.random_bool(0.5)) 
+            {
+                row_cells[x * 2 + 2] = true;
+                let set_old: usize = self.sets[x + 1];
+                let set_new: usize = self.sets[x];
+                for set in self.sets.iter_mut() {
+                    if *set == set_old {
+                        *set = set_new;
+                    }
+                }
+            }
+        }
+        self.pending.push_back(row_cells);
+        if last {
+            self.pending.push_back(vec![false; self.tiles_width()]);
+            self.row += 1;
+            return;
+        }
+
+        // Join vertically, at least once per set.
+        let mut set_members: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (x, set) in self.sets.iter().enumerate() {
+            set_members.entry(*set).or_default().push(x);
+        }
+        let mut row_below: Vec<bool> = vec![false; self.tiles_width()];
+        let mut sets_below: Vec<usize> = vec![0; self.width];
+        // Sorted, so that the RNG is consumed in the same order for the same seed.
+        let mut sets_ordered: Vec<(usize, Vec<usize>)> = set_members.into_iter().collect();
+        sets_ordered.sort_unstable_by_key(|(set, _)| *set);
+        for (set, members) in sets_ordered {
+            let forced: usize = members[self.generator_rng.random_range(0..members.len())];
+            for x in members {
+                if x == forced || self.generator_rng.random_bool(0.5) {
+                    row_below[x * 2 + 1] = true;
+                    sets_below[x] = set;
+                }
+            }
+        }
+        self.pending.push_back(row_below);
+        self.sets = sets_below;
+        self.row += 1;
+    }
+}
+
+impl Iterator for EllerRows {
+    type Item = Vec<bool>;
+
+    fn next(&mut self) -> Option<Vec<bool>> {
+        if !self.started {
+            self.started = true;
+            return Some(vec![false; self.tiles_width()]);
+        }
+        if self.pending.is_empty() && self.row < self.height && self.width > 0 {
+            self.next_cells_row();
+        }
+        self.pending.pop_front()
+    }
+}
+
+/// Text of a tile row, with the UI tiles.
+pub fn row_to_string(row: &[bool]) -> String {
+    row
+        .iter()
+        .map(|path| if *path { ui::LABYRINTH_UI_TILES.on } else { ui::LABYRINTH_UI_TILES.off })
+        .collect()
+}
+
+/// # Labyrinth generator; Eller's.
+/// Same rows as `EllerRows`, written into a grid. Gives a perfect maze.
+/// Rows are made without looking at the grid: with a mask, the whole lattice is made, then fitted to the silhouette by `lattice_mask_fit`.
+pub fn eller(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, Eller's (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let (mut grid_labyrinth, lattice, entrance) = super::lattice_initialize(config);
+    let rows: EllerRows = EllerRows::new(lattice.width, lattice.height, config.seed);
+
+    for (y, row) in rows.enumerate() {
+        for (x, path) in row.into_iter().enumerate() {
+            if path {
+                grid_labyrinth.update_tile(x as i32, y as i32, !config.default_state, Vec::new());
+            }
+        }
+    }
+    if !lattice.is_empty() {
+        super::carve_cell(&mut grid_labyrinth, config, entrance, vec![grid::TileFeatures::Named("Entrance")]);
+    }
+    let mut fit_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    super::lattice_mask_fit(&mut grid_labyrinth, config, &lattice, entrance, &mut fit_rng);
+    if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+        grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+    }
+
+    Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance))
+}
+
+/// `MazeGenerator` of `eller`.
+pub struct Eller;
+
+impl MazeGenerator for Eller {
+    fn name(&self) -> &'static str {
+        "eller"
+    }
+
+    fn description(&self) -> &'static str {
+        "Eller's, row by row; perfect maze, can be streamed to a file at any height."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        eller(config, labyrinth_ui_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tiles of the rows, `true` for a path.
+    fn rows(width: usize, height: usize, seed: u64) -> Vec<Vec<bool>> {
+        EllerRows::new(width, height, seed).collect()
+    }
+
+    #[test]
+    fn rows_shape() {
+        let tiles: Vec<Vec<bool>> = rows(7, 5, 3);
+        assert_eq!(tiles.len(), 11);
+        assert!(tiles.iter().all(|row| row.len() == 15 && !row[0] && !row[14]));
+        assert!(!tiles[0].iter().any(|path| *path));
+        assert!(!tiles[10].iter().any(|path| *path));
+    }
+
+    #[test]
+    fn rows_perfect_maze() {
+        for seed in 0..20u64 {
+            let (width, height): (usize, usize) = (9, 6);
+            let tiles: Vec<Vec<bool>> = rows(width, height, seed);
+            let cells: usize = width * height;
+            // A tree of the cells: one passage less than the cells...
+            let paths: usize = tiles.iter().flatten().filter(|path| **path).count();
+            assert_eq!(paths, cells * 2 - 1, "seed {}", seed);
+            // ...and all of them connected.
+            let mut reached: Vec<Vec<bool>> = vec![vec![false; width * 2 + 1]; height * 2 + 1];
+            let mut stack: Vec<(usize, usize)> = vec![(1, 1)];
+            reached[1][1] = true;
+            let mut counter: usize = 1;
+            while let Some((x, y)) = stack.pop() {
+                for (x_next, y_next) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                    if tiles[y_next][x_next] && !reached[y_next][x_next] {
+                        reached[y_next][x_next] = true;
+                        counter += 1usize;
+                        stack.push((x_next, y_next));
+                    }
+                }
+            }
+            assert_eq!(counter, paths, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn rows_same_seed() {
+        assert_eq!(rows(8, 8, 42), rows(8, 8, 42));
+    }
+
+    #[test]
+    fn perfect_maze() {
+        super::super::tests::assert_perfect(&Eller, super::super::tests::config_test());
+    }
+}
@@ -0,0 +1,124 @@
+// LABYRINTH
+// Generators - Growing tree
+
+use super::super::{HashMap, seq::IndexedRandom, Rng, SeedableRng, StdRng};
+use super::super::{basics, ui, grid};
+use super::{MazeGenerator, Lattice, Labyrinth};
+
+/// Index in the active list of the cell to carve from, according to the policy.
+fn select_index(cell_selection: basics::CellSelection, active_len: usize, generator_rng: &mut StdRng) -> usize {
+    match cell_selection {
+        basics::CellSelection::Newest => active_len - 1,
+        basics::CellSelection::Oldest => 0,
+        basics::CellSelection::Random => generator_rng.random_range(0..active_len),
+        basics::CellSelection::Middle => active_len / 2,
+        basics::CellSelection::Mix { newest, oldest, random, middle } => {
+            let mut roll: f32 = generator_rng.random::<f32>() * (newest + oldest + random + middle);
+            let policies: [(f32, basics::CellSelection); 4] = [
+                (newest, basics::CellSelection::Newest),
+                (oldest, basics::CellSelection::Oldest),
+                (random, basics::CellSelection::Random),
+                (middle, basics::CellSelection::Middle),
+            ];
+            for (weight, policy) in policies {
+                if weight > 0f32 && roll < weight {
+                    return select_index(policy, active_len, generator_rng);
+                }
+                roll -= weight;
+            }
+            active_len - 1
+        },
+    }
+}
+
+/// # Labyrinth generator; growing tree.
+/// Keep a list of active cells; carve from one of them, chosen by `config.cell_selection`, toward a random unvisited neighbour, and drop it once it has none.
+/// Newest gives the backtracker, random gives a Prim-like texture, and mixes are in between.
+/// Iteration limit to zero to disable the limit.
+pub fn growing_tree(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, growing tree (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let (mut grid_labyrinth, lattice, entrance) = super::lattice_initialize(config);
+    if lattice.is_empty() {
+        return Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance));
+    }
+
+    let mut visited: Vec<bool> = vec![false; lattice.len()];
+    visited[lattice.index(entrance)] = true;
+    let mut active: Vec<basics::Position> = vec![entrance];
+    let mut counter: usize = 0;
+
+    while !active.is_empty() && (config.iteration_limit < 1 || counter < config.iteration_limit) {
+        counter += 1usize;
+        let active_index: usize = select_index(config.cell_selection, active.len(), &mut generator_rng);
+        let cell: basics::Position = active[active_index];
+        let unvisited: Vec<basics::Position> = lattice
+            .neighbours(cell)
+            .into_iter()
+            .map(|(_, neighbour)| neighbour)
+            .filter(|neighbour| !visited[lattice.index(*neighbour)])
+            .collect();
+
+        match unvisited.choose(&mut generator_rng) {
+            Some(&next) => {
+                super::carve_passage(&mut grid_labyrinth, config, cell, next);
+                visited[lattice.index(next)] = true;
+                active.push(next);
+            },
+            None => {
+                active.remove(active_index);
+            },
+        }
+
+        if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+            grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+        }
+    }
+    if config.iteration_limit >= 1 && counter >= config.iteration_limit {
+        ui::dp(format!("- Iteration limit reached ({}).\n", config.iteration_limit), ui::DebugLogging::Minimal);
+    }
+
+    Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance))
+}
+
+/// `MazeGenerator` of `growing_tree`.
+pub struct GrowingTree;
+
+impl MazeGenerator for GrowingTree {
+    fn name(&self) -> &'static str {
+        "growing_tree"
+    }
+
+    fn description(&self) -> &'static str {
+        "Growing tree; from DFS-like to Prim-like with the cell selection policy."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        growing_tree(config, labyrinth_ui_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfect_maze_every_policy() {
+        for cell_selection in [
+            basics::CellSelection::Newest,
+            basics::CellSelection::Oldest,
+            basics::CellSelection::Random,
+            basics::CellSelection::Middle,
+            basics::CellSelection::Mix { newest: 3f32, oldest: 0f32, random: 1f32, middle: 1f32 },
+        ] {
+            super::super::tests::assert_perfect(&GrowingTree, super::super::tests::config_test().cell_selection(cell_selection));
+        }
+    }
+}
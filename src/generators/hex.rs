@@ -0,0 +1,149 @@
+// LABYRINTH
+// Generators - Hexagonal tiles, sigma labyrinths
+
+use super::super::{HashMap, seq::IndexedRandom, Rng, SeedableRng, StdRng};
+use super::super::{basics, ui, grid};
+use super::{MazeGenerator, Labyrinth};
+
+/// # Walker of `hex_memory_based`, on an existing grid.
+/// Same walker as `random_memory_walk`, with the six `HexDirections` and their arcs instead of the square ones. No islets, nor unsubordination.
+/// A path can only go straight, or turn by 60°: a sharper turn would see the tile it comes from.
+pub fn hex_memory_walk(
+    grid_labyrinth: &mut grid::Grid,
+    start: basics::Position,
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) {
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let mut generator_path: Vec<basics::Position> = vec![start];
+    let mut generator_index: usize = 0;
+    let mut counter: usize = 0;
+
+    while generator_index < generator_path.len() && (config.iteration_limit < 1 || counter < config.iteration_limit) {
+        counter += 1usize;
+        let generator_position: basics::Position = generator_path[generator_index];
+        let available_directions: Vec<basics::HexDirections> = basics::HexDirections::ALL
+            .iter()
+            .copied()
+            .filter(|direction| {
+                let next: basics::Position = direction.step(generator_position);
+                matches!(super::state_tile(grid_labyrinth, config, next.x, next.y), grid::TileState::Off)
+                    && direction.arc().iter().all(|neighbour| {
+                        let tile: basics::Position = neighbour.step(next);
+                        matches!(super::state_tile(grid_labyrinth, config, tile.x, tile.y), grid::TileState::Off)
+                    })
+            })
+            .collect();
+
+        match available_directions.choose(&mut generator_rng) {
+            Some(direction) => {
+                let next: basics::Position = direction.step(generator_position);
+                grid_labyrinth.update_tile(next.x, next.y, !config.default_state, Vec::new());
+                generator_path.push(next);
+                generator_index = generator_path.len() - 1;
+            },
+            None => {
+                generator_path.remove(generator_index);
+                if generator_path.is_empty() {
+                    ui::dp(String::from("- Reached end.\n"), ui::DebugLogging::Minimal);
+                    break;
+                }
+                generator_index = match config.stuck_reaction {
+                    basics::StuckReaction::RandomPosition => generator_rng.random_range(0..generator_path.len()),
+                    basics::StuckReaction::OneStepBack => generator_index.saturating_sub(1),
+                };
+            },
+        }
+
+        if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+            grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+        }
+    }
+    if config.iteration_limit >= 1 && counter >= config.iteration_limit {
+        ui::dp(format!("- Iteration limit reached ({}).\n", config.iteration_limit), ui::DebugLogging::Minimal);
+    }
+}
+
+/// # Labyrinth generator; memory based, on hexagonal tiles.
+/// `hex_memory_walk` from the start tile. The grid holds the tiles in rows, odd rows being shifted East by half a tile.
+pub fn hex_memory_based(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, hexagonal (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let mut grid_labyrinth: grid::Grid = super::grid_initialize(config, config.default_state);
+    let start: basics::Position = super::start_tile(config);
+    grid_labyrinth.update_tile(start.x, start.y, !config.default_state, vec![grid::TileFeatures::Named("Entrance")]);
+    hex_memory_walk(&mut grid_labyrinth, start, config, labyrinth_ui_features);
+
+    let mut labyrinth: Labyrinth = Labyrinth::new(grid_labyrinth, config, start);
+    labyrinth.tessellation = basics::Tessellation::Hexagons;
+    labyrinth
+}
+
+/// `MazeGenerator` of `hex_memory_based`.
+pub struct HexMemoryBased;
+
+impl MazeGenerator for HexMemoryBased {
+    fn name(&self) -> &'static str {
+        "hex_memory_based"
+    }
+
+    fn description(&self) -> &'static str {
+        "Random walker on hexagonal tiles, six directions; sigma labyrinth."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        hex_memory_based(config, labyrinth_ui_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directions_opposite() {
+        for tile in [basics::Position { x: 4, y: 4 }, basics::Position { x: 4, y: 5 }] {
+            for direction in basics::HexDirections::ALL {
+                assert!(direction.opposite().step(direction.step(tile)) == tile);
+            }
+        }
+    }
+
+    #[test]
+    fn tree_of_hexagons() {
+        for seed in 0..8u64 {
+            let config: basics::GeneratorConfig = super::super::tests::config_test().seed(seed);
+            let labyrinth: Labyrinth = hex_memory_based(&config, &HashMap::new());
+            let is_path = |tile: basics::Position| tile.x >= 0 && tile.y >= 0 && matches!(labyrinth.state_tile(tile.x, tile.y), grid::TileState::On);
+            let paths: Vec<basics::Position> = (0..config.height as i32)
+                .flat_map(|y| (0..config.width as i32).map(move |x| basics::Position { x, y }))
+                .filter(|tile| is_path(*tile))
+                .collect();
+            // Each link seen from both of its tiles.
+            let links: usize = paths
+                .iter()
+                .map(|tile| basics::HexDirections::ALL.iter().filter(|direction| is_path(direction.step(*tile))).count())
+                .sum::<usize>() / 2;
+            let mut reached: Vec<basics::Position> = vec![labyrinth.entrance];
+            let mut stack: Vec<basics::Position> = vec![labyrinth.entrance];
+            while let Some(tile) = stack.pop() {
+                for direction in basics::HexDirections::ALL {
+                    let next: basics::Position = direction.step(tile);
+                    if is_path(next) && !reached.contains(&next) {
+                        reached.push(next);
+                        stack.push(next);
+                    }
+                }
+            }
+            assert!(paths.len() > 1, "seed {}", seed);
+            assert_eq!(links, paths.len() - 1, "seed {}", seed);
+            assert_eq!(reached.len(), paths.len(), "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+// LABYRINTH
+// Generators - Hunt-and-kill
+
+use super::super::{HashMap, seq::IndexedRandom, SeedableRng, StdRng};
+use super::super::{basics, ui, grid};
+use super::{MazeGenerator, Lattice, Labyrinth};
+
+/// First unvisited cell next to a visited one, scanning rows from `row_first`; with a visited neighbour to join.
+/// Also gives the first row still holding unvisited cells, so the next hunt skips the completed rows.
+fn hunt(
+    lattice: &Lattice, 
+    visited: &[bool], 
+    row_first: usize, 
+    generator_rng: &mut StdRng
+) -> (Option<(basics::Position, basics::Position)>, usize) {
+    let mut row_incomplete: Option<usize> = None;
+    for y in row_first..lattice.height {
+        for x in 0..lattice.width {
+            let cell: basics::Position = basics::Position { x: x as i32, y: y as i32 };
+            if visited[lattice.index(cell)] || !lattice.contains(cell) {
+                continue;
+            }
+            row_incomplete.get_or_insert(y);
+            let visited_neighbours: Vec<basics::Position> = lattice
+                .neighbours(cell)
+                .into_iter()
+                .map(|(_, neighbour)| neighbour)
+                .filter(|neighbour| visited[lattice.index(*neighbour)])
+                .collect();
+            if let Some(&from) = visited_neighbours.choose(generator_rng) {
+                return (Some((from, cell)), row_incomplete.unwrap_or(y));
+            }
+        }
+    }
+    (None, row_incomplete.unwrap_or(lattice.height))
+}
+
+/// # Labyrinth generator; hunt-and-kill.
+/// Walk randomly toward unvisited cells; when stuck, scan the lattice for an unvisited cell touching the maze and restart from there.
+/// A deterministic alternative to `StuckReaction::RandomPosition`, using no memory of the path.
+/// Iteration limit to zero to disable the limit.
+pub fn hunt_and_kill(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, hunt-and-kill (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let (mut grid_labyrinth, lattice, entrance) = super::lattice_initialize(config);
+    if lattice.is_empty() {
+        return Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance));
+    }
+
+    let mut visited: Vec<bool> = vec![false; lattice.len()];
+    visited[lattice.index(entrance)] = true;
+    let mut cell: basics::Position = entrance;
+    let mut row_first: usize = 0;
+    let mut counter: usize = 0;
+
+    while config.iteration_limit < 1 || counter < config.iteration_limit {
+        counter += 1usize;
+        let unvisited: Vec<basics::Position> = lattice
+            .neighbours(cell)
+            .into_iter()
+            .map(|(_, neighbour)| neighbour)
+            .filter(|neighbour| !visited[lattice.index(*neighbour)])
+            .collect();
+
+        let (from, next) = match unvisited.choose(&mut generator_rng) {
+            // Kill
+            Some(&next) => (cell, next),
+            // Hunt
+            None => {
+                let (found, row_incomplete) = hunt(&lattice, &visited, row_first, &mut generator_rng);
+                row_first = row_incomplete;
+                match found {
+                    Some(pair) => pair,
+                    None => break,
+                }
+            },
+        };
+        super::carve_passage(&mut grid_labyrinth, config, from, next);
+        visited[lattice.index(next)] = true;
+        cell = next;
+
+        if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+            grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+        }
+    }
+    if config.iteration_limit >= 1 && counter >= config.iteration_limit {
+        ui::dp(format!("- Iteration limit reached ({}).\n", config.iteration_limit), ui::DebugLogging::Minimal);
+    }
+
+    Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance))
+}
+
+/// `MazeGenerator` of `hunt_and_kill`.
+pub struct HuntAndKill;
+
+impl MazeGenerator for HuntAndKill {
+    fn name(&self) -> &'static str {
+        "hunt_and_kill"
+    }
+
+    fn description(&self) -> &'static str {
+        "Hunt-and-kill; random walk, scanning for the maze's edge when stuck; long corridors."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        hunt_and_kill(config, labyrinth_ui_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfect_maze() {
+        super::super::tests::assert_perfect(&HuntAndKill, super::super::tests::config_test());
+    }
+}
@@ -0,0 +1,111 @@
+// LABYRINTH
+// Generators - Randomized Kruskal's
+
+use super::super::{HashMap, seq::SliceRandom, SeedableRng, StdRng};
+use super::super::{basics, ui, grid};
+use super::{MazeGenerator, Lattice, Labyrinth};
+
+/// Wall between two adjacent cells of the lattice, that can be removed.
+#[derive(Clone, Copy)]
+pub struct Edge {
+    pub from: basics::Position,
+    pub to: basics::Position,
+}
+
+/// Every wall between two adjacent cells; each one is listed once, toward East or North.
+pub fn lattice_edges(lattice: &Lattice) -> Vec<Edge> {
+    let mut edges: Vec<Edge> = Vec::with_capacity(lattice.len() * 2);
+    for index in 0..lattice.len() {
+        let cell: basics::Position = lattice.cell(index);
+        for direction in [basics::OrdinalDirections::East, basics::OrdinalDirections::North] {
+            let offset: basics::Position = direction.offset();
+            let neighbour: basics::Position = basics::Position { x: cell.x + offset.x, y: cell.y + offset.y };
+            if lattice.contains(cell) && lattice.contains(neighbour) {
+                edges.push(Edge { from: cell, to: neighbour });
+            }
+        }
+    }
+    edges
+}
+
+/// # Kruskal's, on an ordered list of edges.
+/// Remove each wall in order if it joins two cells that are not yet connected, tracked by a disjoint-set. Gives a perfect maze.
+/// Iteration limit to zero to disable the limit.
+pub fn kruskal_carve(
+    grid_labyrinth: &mut grid::Grid,
+    lattice: &Lattice,
+    entrance: basics::Position,
+    config: &basics::GeneratorConfig,
+    edges: &[Edge],
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) {
+    let mut sets: basics::DisjointSet = basics::DisjointSet::new(lattice.len());
+
+    for (counter, edge) in edges.iter().enumerate() {
+        if config.iteration_limit >= 1 && counter >= config.iteration_limit {
+            ui::dp(format!("- Iteration limit reached ({}).\n", config.iteration_limit), ui::DebugLogging::Minimal);
+            break;
+        }
+        if sets.union(lattice.index(edge.from), lattice.index(edge.to)) {
+            let wall: basics::Position = Lattice::wall(edge.from, edge.to);
+            grid_labyrinth.update_tile(wall.x, wall.y, !config.default_state, Vec::new());
+            // Do not overwrite the entrance's features.
+            for cell in [edge.from, edge.to] {
+                if cell != entrance {
+                    super::carve_cell(grid_labyrinth, config, cell, Vec::new());
+                }
+            }
+
+            if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+                grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+            }
+        }
+    }
+}
+
+/// # Labyrinth generator; randomized Kruskal's.
+/// `kruskal_carve` with the edges in a random order.
+pub fn randomized_kruskal(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, Kruskal's (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let (mut grid_labyrinth, lattice, entrance) = super::lattice_initialize(config);
+    let mut edges: Vec<Edge> = lattice_edges(&lattice);
+    edges.shuffle(&mut generator_rng);
+
+    kruskal_carve(&mut grid_labyrinth, &lattice, entrance, config, &edges, labyrinth_ui_features);
+    Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance))
+}
+
+/// `MazeGenerator` of `randomized_kruskal`.
+pub struct Kruskal;
+
+impl MazeGenerator for Kruskal {
+    fn name(&self) -> &'static str {
+        "kruskal"
+    }
+
+    fn description(&self) -> &'static str {
+        "Randomized Kruskal's with union-find; perfect maze, many short dead ends."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        randomized_kruskal(config, labyrinth_ui_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfect_maze() {
+        super::super::tests::assert_perfect(&Kruskal, super::super::tests::config_test());
+    }
+}
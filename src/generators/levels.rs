@@ -0,0 +1,223 @@
+// LABYRINTH
+// Generators - Levels stacked and joined by stairs
+
+use std::collections::{VecDeque, hash_map::Entry};
+use super::super::{HashMap, seq::IndexedRandom, Rng, StdRng};
+use super::super::{basics, ui, grid, post_processing};
+use super::Labyrinth;
+
+/// # Labyrinth of several levels, the same size, joined by stairs.
+/// The level 0 holds the entrance; every other level's entrance is the stairs first reaching it.
+pub struct Levels {
+    pub levels: Vec<Labyrinth>,
+    /// Stairs, as the lower level and the tile: they join the tile to the same one on the level above.
+    pub stairs: Vec<(usize, basics::Position)>,
+}
+
+impl Levels {
+    /// Path tiles next to the tile: the 4 neighbours on its level, and the other end of its stairs.
+    fn neighbours(&self, level: usize, tile: basics::Position) -> Vec<(usize, basics::Position)> {
+        let mut neighbours: Vec<(usize, basics::Position)> = basics::OrdinalDirections::ALL
+            .iter()
+            .map(|direction| {
+                let offset: basics::Position = direction.offset();
+                (level, basics::Position { x: tile.x + offset.x, y: tile.y + offset.y })
+            })
+            .filter(|(level, neighbour)| matches!(self.levels[*level].state_tile(neighbour.x, neighbour.y), grid::TileState::On))
+            .collect();
+        for (stairs_level, stairs_tile) in &self.stairs {
+            if *stairs_tile == tile && *stairs_level == level {
+                neighbours.push((level + 1, tile));
+            } else if *stairs_tile == tile && *stairs_level + 1 == level {
+                neighbours.push((level - 1, tile));
+            }
+        }
+        neighbours
+    }
+
+    /// # Exit on any level, like `post_processing::place_exit`: the farthest path tile from the entrance, or an opening in the outer wall, tagged `Named("Exit")`.
+    /// Gives the level, the exit, and the length of the shortest path to it, in steps; stairs count as one step.
+    /// When no path can be opened to the outside, the exit falls back to the farthest tile.
+    pub fn place_exit(
+        &mut self, 
+        config: &basics::GeneratorConfig, 
+        placement: post_processing::ExitPlacement
+    ) -> Option<(usize, basics::Position, usize)> {
+        let entrance: basics::Position = self.levels.first()?.entrance;
+        let mut tiles_distance: HashMap<(usize, basics::Position), usize> = HashMap::from([((0, entrance), 0)]);
+        let mut queue: VecDeque<(usize, basics::Position)> = VecDeque::from([(0, entrance)]);
+        // Level, tiles to carve, the exit last, and its distance.
+        let mut farthest: Option<(usize, Vec<basics::Position>, usize)> = None;
+        while let Some((level, tile)) = queue.pop_front() {
+            let distance: usize = tiles_distance[&(level, tile)];
+            let candidate: Option<Vec<basics::Position>> = match placement {
+                post_processing::ExitPlacement::None => None,
+                post_processing::ExitPlacement::Farthest => Some(vec![tile]).filter(|_| distance > 0),
+                post_processing::ExitPlacement::Border => basics::OrdinalDirections::ALL
+                    .iter()
+                    .filter_map(|direction| post_processing::border_walls(&self.levels[level], tile, *direction))
+                    .min_by_key(|walls| walls.len()),
+            };
+            if let Some(walls) = candidate {
+                let exit_distance: usize = distance + walls.len() - usize::from(placement == post_processing::ExitPlacement::Farthest);
+                if farthest.as_ref().is_none_or(|(_, _, farthest_distance)| exit_distance > *farthest_distance) {
+                    farthest = Some((level, walls, exit_distance));
+                }
+            }
+            for neighbour in self.neighbours(level, tile) {
+                if let Entry::Vacant(slot) = tiles_distance.entry(neighbour) {
+                    slot.insert(distance + 1);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        let Some((level, tiles, exit_distance)) = farthest else {
+            if placement == post_processing::ExitPlacement::Border {
+                println!("(!) - No path can be opened to the outside: exit at the farthest tile instead.");
+                return self.place_exit(config, post_processing::ExitPlacement::Farthest);
+            }
+            return None;
+        };
+        let exit: basics::Position = *tiles.last()?;
+        for tile in &tiles {
+            let features: Vec<grid::TileFeatures> = if *tile == exit { vec![grid::TileFeatures::Named("Exit")] } else { Vec::new() };
+            self.levels[level].grid.update_tile(tile.x, tile.y, !config.default_state, features);
+        }
+        self.levels[level].exits.push(exit);
+        ui::dp(format!("- Exit: level {}, x={}, y={}; path length: {}.\n", level, exit.x, exit.y, exit_distance), ui::DebugLogging::Minimal);
+        Some((level, exit, exit_distance))
+    }
+}
+
+/// If stairs can arrive on the tile: a wall, with nothing but walls around it.
+fn stairs_free(grid_labyrinth: &grid::Grid, config: &basics::GeneratorConfig, tile: basics::Position) -> bool {
+    (-1..=1).all(|offset_y| (-1..=1).all(|offset_x| {
+        matches!(super::state_tile(grid_labyrinth, config, tile.x + offset_x, tile.y + offset_y), grid::TileState::Off)
+    }))
+}
+
+/// # Labyrinth generator; memory based, on levels joined by stairs.
+/// The walker of `random_memory_based`, islets, weave and bias included, with one more move: at each step, with the probability `config.stairs`,
+/// go up or down a level, where the tile above or below is free, and carry on from there.
+/// The levels are walked side by side in one grid, a column of `Void` apart, then split.
+/// Stairs are tagged `Named("Up")` on the lower level and `Named("Down")` on the upper one.
+/// Iteration limit to zero to disable the limit.
+pub fn levels_memory_based(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Levels {
+    ui::dp(format!("## Generating labyrinth, {} levels (seed {}).\n", config.levels, config.seed), ui::DebugLogging::Minimal);
+    let levels_count: usize = config.levels.max(1);
+    // Offset from a level to the next one in the grid of all levels.
+    let stride: i32 = config.width as i32 + 1;
+    let config_all: basics::GeneratorConfig = config.clone()
+        .width(config.width * levels_count + levels_count - 1)
+        .mask(Some(basics::Mask::side_by_side(config.mask.as_ref(), config.width, config.height, levels_count)));
+    let mut grid_all: grid::Grid = super::grid_initialize(&config_all, config.default_state);
+    let start: basics::Position = super::start_tile(config);
+    grid_all.update_tile(start.x, start.y, !config.default_state, vec![grid::TileFeatures::Named("Entrance")]);
+
+    let mut entrances: Vec<Option<basics::Position>> = vec![None; levels_count];
+    let mut stairs: Vec<(usize, basics::Position)> = Vec::new();
+    entrances[0] = Some(start);
+    let crossings: Vec<basics::Position> = {
+        // Stairs, never on the entrance nor on other stairs.
+        let mut stairs_move = |grid_all: &mut grid::Grid, position: basics::Position, generator_rng: &mut StdRng| -> Option<basics::Position> {
+            let level: usize = (position.x / stride) as usize;
+            let tile: basics::Position = basics::Position { x: position.x % stride, y: position.y };
+            if levels_count < 2 
+                || tile == start 
+                || stairs.iter().any(|(_, stairs_tile)| *stairs_tile == tile)
+                || generator_rng.random::<f32>() >= config.stairs
+            {
+                return None;
+            }
+            let at = |level: usize| basics::Position { x: level as i32 * stride + tile.x, y: tile.y };
+            let targets: Vec<usize> = [level.checked_sub(1), Some(level + 1).filter(|above| *above < levels_count)]
+                .into_iter()
+                .flatten()
+                .filter(|target| stairs_free(grid_all, &config_all, at(*target)))
+                .collect();
+            let target: usize = *targets.choose(generator_rng)?;
+            stairs.push((level.min(target), tile));
+            entrances[target].get_or_insert(tile);
+            grid_all.update_tile(at(target).x, at(target).y, !config.default_state, Vec::new());
+            ui::dp(format!("- Stairs from level {} to {}, x={}, y={}.\n", level, target, tile.x, tile.y), ui::DebugLogging::All);
+            Some(at(target))
+        };
+        super::random_memory_walk(&mut grid_all, start, &config_all, labyrinth_ui_features, Some(&mut stairs_move))
+    };
+
+    // Split, each level in a grid of its own.
+    let mut levels: Vec<Labyrinth> = (0..levels_count)
+        .map(|level| {
+            let mut grid_labyrinth: grid::Grid = super::grid_initialize(config, config.default_state);
+            for y in 0..config.height as i32 {
+                for x in 0..config.width as i32 {
+                    if matches!(grid_all.state_tile(level as i32 * stride + x, y), grid::TileState::On) {
+                        grid_labyrinth.update_tile(x, y, !config.default_state, Vec::new());
+                    }
+                }
+            }
+            Labyrinth::new(grid_labyrinth, config, entrances[level].unwrap_or(start))
+        })
+        .collect();
+    levels[0].grid.update_tile(start.x, start.y, !config.default_state, vec![grid::TileFeatures::Named("Entrance")]);
+    for crossing in crossings {
+        let level: usize = (crossing.x / stride) as usize;
+        let tile: basics::Position = basics::Position { x: crossing.x % stride, y: crossing.y };
+        levels[level].grid.update_tile(tile.x, tile.y, !config.default_state, vec![grid::TileFeatures::Named("Crossing")]);
+        levels[level].tags.push((tile, grid::TileFeatures::Named("Crossing")));
+    }
+    for (lower, tile) in &stairs {
+        levels[*lower].tags.push((*tile, grid::TileFeatures::Named("Up")));
+        levels[lower + 1].tags.push((*tile, grid::TileFeatures::Named("Down")));
+    }
+    for (level, entrance) in entrances.iter().enumerate() {
+        if entrance.is_none() {
+            ui::dp(format!("(!) - Level {} never reached by stairs.\n", level), ui::DebugLogging::Minimal);
+        }
+    }
+
+    Levels { levels, stairs }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_reached_through_stairs() {
+        for stairs in [0.05f32, 0.2f32, 0.5f32] {
+            for seed in 0..8u64 {
+                let config: basics::GeneratorConfig = basics::GeneratorConfig::new().width(21).height(13).seed(seed).levels(3).stairs(stairs);
+                let levels: Levels = levels_memory_based(&config, &HashMap::new());
+                assert_eq!(levels.levels.len(), 3);
+                let entrance: (usize, basics::Position) = (0, levels.levels[0].entrance);
+                let mut reached: Vec<(usize, basics::Position)> = vec![entrance];
+                let mut stack: Vec<(usize, basics::Position)> = vec![entrance];
+                while let Some((level, tile)) = stack.pop() {
+                    for neighbour in levels.neighbours(level, tile) {
+                        if !reached.contains(&neighbour) {
+                            reached.push(neighbour);
+                            stack.push(neighbour);
+                        }
+                    }
+                }
+                for (level, labyrinth) in levels.levels.iter().enumerate() {
+                    let mut paths: usize = 0usize;
+                    for y in 0..config.height as i32 {
+                        for x in 0..config.width as i32 {
+                            if matches!(labyrinth.state_tile(x, y), grid::TileState::On) {
+                                paths += 1usize;
+                                assert!(reached.contains(&(level, basics::Position { x, y })), "level {}, x={}, y={}, seed {}", level, x, y, seed);
+                            }
+                        }
+                    }
+                    assert!(paths > 0, "level {} empty, stairs {}, seed {}", level, stairs, seed);
+                }
+            }
+        }
+    }
+}
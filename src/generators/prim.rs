@@ -0,0 +1,94 @@
+// LABYRINTH
+// Generators - Randomized Prim's
+
+use super::super::{HashMap, seq::IndexedRandom, Rng, SeedableRng, StdRng};
+use super::super::{basics, ui, grid};
+use super::{MazeGenerator, Lattice, Labyrinth};
+
+/// # Labyrinth generator; randomized Prim's, frontier based.
+/// Keep the frontier of unvisited cells touching the maze; take one at random and join it to a random visited neighbour. Gives a perfect maze, short corridors and a lot of branches.
+/// Iteration limit to zero to disable the limit.
+pub fn randomized_prim(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, Prim's (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let (mut grid_labyrinth, lattice, entrance) = super::lattice_initialize(config);
+    if lattice.is_empty() {
+        return Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance));
+    }
+
+    let mut visited: Vec<bool> = vec![false; lattice.len()];
+    let mut in_frontier: Vec<bool> = vec![false; lattice.len()];
+    let mut frontier: Vec<basics::Position> = Vec::new();
+    let mut counter: usize = 0;
+
+    visited[lattice.index(entrance)] = true;
+    for (_, neighbour) in lattice.neighbours(entrance) {
+        in_frontier[lattice.index(neighbour)] = true;
+        frontier.push(neighbour);
+    }
+
+    while !frontier.is_empty() && (config.iteration_limit < 1 || counter < config.iteration_limit) {
+        counter += 1usize;
+        let cell: basics::Position = frontier.swap_remove(generator_rng.random_range(0..frontier.len()));
+
+        let mut visited_neighbours: Vec<basics::Position> = Vec::new();
+        for (_, neighbour) in lattice.neighbours(cell) {
+            let neighbour_index: usize = lattice.index(neighbour);
+            if visited[neighbour_index] {
+                visited_neighbours.push(neighbour);
+            } else if !in_frontier[neighbour_index] {
+                in_frontier[neighbour_index] = true;
+                frontier.push(neighbour);
+            }
+        }
+
+        let &from: &basics::Position = visited_neighbours
+            .choose(&mut generator_rng)
+            .expect("(!) - Frontier cell without visited neighbour.");
+        super::carve_passage(&mut grid_labyrinth, config, from, cell);
+        visited[lattice.index(cell)] = true;
+
+        if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+            grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+        }
+    }
+    if config.iteration_limit >= 1 && counter >= config.iteration_limit {
+        ui::dp(format!("- Iteration limit reached ({}).\n", config.iteration_limit), ui::DebugLogging::Minimal);
+    }
+
+    Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance))
+}
+
+/// `MazeGenerator` of `randomized_prim`.
+pub struct Prim;
+
+impl MazeGenerator for Prim {
+    fn name(&self) -> &'static str {
+        "prim"
+    }
+
+    fn description(&self) -> &'static str {
+        "Randomized Prim's; perfect maze, short corridors, highly branching."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        randomized_prim(config, labyrinth_ui_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfect_maze() {
+        super::super::tests::assert_perfect(&Prim, super::super::tests::config_test());
+    }
+}
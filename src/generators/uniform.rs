@@ -0,0 +1,173 @@
+// LABYRINTH
+// Generators - Uniform spanning trees: Wilson's and Aldous-Broder
+
+use super::super::{HashMap, seq::IndexedRandom, SeedableRng, StdRng};
+use super::super::{basics, ui, grid};
+use super::{MazeGenerator, Lattice, Labyrinth};
+
+/// Random adjacent cell of the lattice.
+fn random_neighbour(lattice: &Lattice, cell: basics::Position, generator_rng: &mut StdRng) -> basics::Position {
+    lattice
+        .neighbours(cell)
+        .choose(generator_rng)
+        .expect("(!) - Cell without neighbour.")
+        .1
+}
+
+/// # Labyrinth generator; Wilson's, loop-erased random walks.
+/// From each cell outside the maze, walk randomly until the maze is hit, only remembering the last exit of each cell, so loops are erased; then carve the walk. 
+/// Samples uniformly among all perfect mazes.
+/// Iteration limit (steps of the walks) to zero to disable the limit.
+pub fn wilson(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, Wilson's (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let (mut grid_labyrinth, lattice, entrance) = super::lattice_initialize(config);
+    if lattice.len() < 2 {
+        return Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance));
+    }
+
+    // Walks only start from cells the maze can reach; a mask may cut some off.
+    let reachable: Vec<bool> = lattice.reachable(entrance);
+    let mut in_maze: Vec<bool> = vec![false; lattice.len()];
+    let mut walk_exits: Vec<basics::Position> = vec![entrance; lattice.len()];
+    let mut counter: usize = 0;
+    in_maze[lattice.index(entrance)] = true;
+
+    for walk_start_index in 0..lattice.len() {
+        if in_maze[walk_start_index] || !reachable[walk_start_index] {
+            continue;
+        }
+        // Walk, overwriting exits: loops vanish by themselves.
+        let walk_start: basics::Position = lattice.cell(walk_start_index);
+        let mut cell: basics::Position = walk_start;
+        while !in_maze[lattice.index(cell)] {
+            if config.iteration_limit >= 1 && counter >= config.iteration_limit {
+                ui::dp(format!("- Iteration limit reached ({}).\n", config.iteration_limit), ui::DebugLogging::Minimal);
+                return Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance));
+            }
+            counter += 1usize;
+            let next: basics::Position = random_neighbour(&lattice, cell, &mut generator_rng);
+            walk_exits[lattice.index(cell)] = next;
+            cell = next;
+        }
+        // Carve the loop-erased walk, backward from the maze so the cell `from` is always carved.
+        let mut walk: Vec<basics::Position> = vec![walk_start];
+        cell = walk_start;
+        while !in_maze[lattice.index(cell)] {
+            in_maze[lattice.index(cell)] = true;
+            cell = walk_exits[lattice.index(cell)];
+            walk.push(cell);
+        }
+        for pair in walk.windows(2).rev() {
+            super::carve_passage(&mut grid_labyrinth, config, pair[1], pair[0]);
+        }
+
+        if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+            grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+        }
+    }
+
+    Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance))
+}
+
+/// # Labyrinth generator; Aldous-Broder, random walk.
+/// Walk randomly over the whole lattice, carving only when entering a cell for the first time. 
+/// Samples uniformly among all perfect mazes, but slowly; kept as the reference for Wilson's.
+/// Iteration limit (steps of the walk) to zero to disable the limit.
+pub fn aldous_broder(
+    config: &basics::GeneratorConfig,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> Labyrinth {
+    ui::dp(format!("## Generating labyrinth, Aldous-Broder (seed {}).\n", config.seed), ui::DebugLogging::Minimal);
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let (mut grid_labyrinth, lattice, entrance) = super::lattice_initialize(config);
+    if lattice.len() < 2 {
+        return Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance));
+    }
+
+    let mut visited: Vec<bool> = vec![false; lattice.len()];
+    let mut remaining: usize = lattice.reachable(entrance).iter().filter(|reached| **reached).count().saturating_sub(1);
+    let mut counter: usize = 0;
+    let mut cell: basics::Position = entrance;
+    visited[lattice.index(entrance)] = true;
+
+    while remaining > 0 && (config.iteration_limit < 1 || counter < config.iteration_limit) {
+        counter += 1usize;
+        let next: basics::Position = random_neighbour(&lattice, cell, &mut generator_rng);
+        if !visited[lattice.index(next)] {
+            super::carve_passage(&mut grid_labyrinth, config, cell, next);
+            visited[lattice.index(next)] = true;
+            remaining -= 1;
+
+            if ui::DEBUG_LOGGING == ui::DebugLogging::All {
+                grid_labyrinth.display_inline(&ui::LABYRINTH_UI_TILES, labyrinth_ui_features);
+            }
+        }
+        cell = next;
+    }
+    if config.iteration_limit >= 1 && counter >= config.iteration_limit {
+        ui::dp(format!("- Iteration limit reached ({}).\n", config.iteration_limit), ui::DebugLogging::Minimal);
+    }
+
+    Labyrinth::new(grid_labyrinth, config, Lattice::tile(entrance))
+}
+
+/// `MazeGenerator` of `wilson`.
+pub struct Wilson;
+
+impl MazeGenerator for Wilson {
+    fn name(&self) -> &'static str {
+        "wilson"
+    }
+
+    fn description(&self) -> &'static str {
+        "Wilson's loop-erased random walks; uniform spanning tree, unbiased."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        wilson(config, labyrinth_ui_features)
+    }
+}
+
+/// `MazeGenerator` of `aldous_broder`.
+pub struct AldousBroder;
+
+impl MazeGenerator for AldousBroder {
+    fn name(&self) -> &'static str {
+        "aldous_broder"
+    }
+
+    fn description(&self) -> &'static str {
+        "Aldous-Broder random walk; uniform spanning tree, slow reference."
+    }
+
+    fn generate(
+        &self,
+        config: &basics::GeneratorConfig,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Labyrinth {
+        aldous_broder(config, labyrinth_ui_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wilson_perfect_maze() {
+        super::super::tests::assert_perfect(&Wilson, super::super::tests::config_test());
+    }
+
+    #[test]
+    fn aldous_broder_perfect_maze() {
+        super::super::tests::assert_perfect(&AldousBroder, super::super::tests::config_test());
+    }
+}
@@ -1,15 +1,18 @@
 // LABYRINTH
 
 // Imports
-use std::{collections::HashMap, io};
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
-use grid::{self, Grid, TileFeatures};
-use rand::{seq, rng, Rng};
+use grid::{self, TileFeatures};
+use rand::{seq, Rng, SeedableRng, rngs::StdRng};
 
 mod basics;
 mod ui;
 mod generators;
 mod file_handler;
+mod post_processing;
+mod topology;
+mod world;
 
 
 
@@ -25,52 +28,401 @@ fn main() {
 
     println!("## Initialization.");
     // Vars
-    let mut labyrinth_size_input: String = String::new();
-    let mut iteration_limit_input: String = String::new();
     let labyrinth_ui_features: HashMap<TileFeatures, &'static str> = HashMap::from([
         (TileFeatures::Named("Entrance"), "IN"),
         (TileFeatures::Named("Exit"), "EX"),
+        (TileFeatures::Named("Room"), "··"),
+        (TileFeatures::Named("Door"), "DR"),
+        (TileFeatures::Named("Up"), "UP"),
+        (TileFeatures::Named("Down"), "DN"),
+        (TileFeatures::Named("Crossing"), "╬╬"),
     ]);
+    let config_default: basics::GeneratorConfig = basics::GeneratorConfig::new();
+    let algorithms_available: Vec<generators::Algorithm> = generators::registry();
     // User input
     println!("## User input.");
-    println!("- Labyrinth size [N+]({}): ", basics::DEFAULT_SIZE);
-    io::stdin()
-        .read_line(&mut labyrinth_size_input)
-        .expect("(X) - Can't read line.");
-    let labyrinth_size: usize = match labyrinth_size_input.trim().parse() {
+    println!("- Algorithms: ");
+    for algorithm in &algorithms_available {
+        println!("  - {}: {}", algorithm.name(), algorithm.description());
+    }
+    let generator_name: String = ui::ask(&format!("- Algorithm [name]({}): ", algorithms_available[0].name()));
+    let algorithm: generators::Algorithm = match generators::find(&generator_name) {
+        Some(algorithm) => algorithm,
+        None => {
+            if !generator_name.is_empty() {
+                println!("(!) - Unknown algorithm \"{}\", using the default.", generator_name);
+            }
+            algorithms_available.into_iter().next().expect("(X) - No algorithm registered.")
+        }
+    };
+    // Settings are only asked where they apply.
+    let world_chosen: bool = matches!(algorithm, generators::Algorithm::World);
+    let tiles: bool = matches!(algorithm, generators::Algorithm::Tiles(_));
+    // The generators built on `random_memory_walk`, the only ones reading islets, weave, unsubordination and bias.
+    let walker: bool = world_chosen || matches!(algorithm.name(), "random_memory_based" | "dungeon");
+    let labyrinth_width: usize = match ui::ask(&format!("- Labyrinth width [N+]({}): ", config_default.width)).parse() {
         Ok(num) => num,
-        Err(_) => basics::DEFAULT_SIZE,
-    };
-    println!("- Iteration limit [0 = No limit/ N+]({}): ", basics::DEFAULT_ITERATION_LIMIT);
-    io::stdin()
-        .read_line(&mut iteration_limit_input)
-        .expect("(X) - Can't read line.");
-    
-    let iteration_limit: usize = match iteration_limit_input.trim().parse() {
+        Err(_) => config_default.width,
+    };
+    let labyrinth_height: usize = match ui::ask("- Labyrinth height [N+](width): ").parse() {
+        Ok(num) => num,
+        Err(_) => labyrinth_width,
+    };
+    let iteration_limit: usize = match ui::ask(&format!("- Iteration limit [0 = No limit/ N+]({}): ", config_default.iteration_limit)).parse() {
         Ok(num) => num,
-        Err(_) => basics::DEFAULT_ITERATION_LIMIT,
+        Err(_) => config_default.iteration_limit,
+    };
+    let seed: u64 = match ui::ask("- Seed [N+/ empty = random]: ").parse() {
+        Ok(num) => num,
+        Err(_) => rand::random::<u64>(),
+    };
+    // Theta labyrinths start from the centre, chunks of the world too.
+    let start: basics::StartPosition = if !world_chosen && algorithm.name() != "theta" {
+        match ui::ask("- Start [C = Centre/ K = Corner/ R = Random/ N, E, S, W = Side, opened as the entrance/ x,y](C): ").to_uppercase().as_str() {
+            "K" => basics::StartPosition::Corner,
+            "R" => basics::StartPosition::Random,
+            "N" => basics::StartPosition::Side(basics::OrdinalDirections::North),
+            "E" => basics::StartPosition::Side(basics::OrdinalDirections::East),
+            "S" => basics::StartPosition::Side(basics::OrdinalDirections::South),
+            "W" => basics::StartPosition::Side(basics::OrdinalDirections::West),
+            text => match text.split_once(',').map(|(x, y)| (x.trim().parse::<i32>(), y.trim().parse::<i32>())) {
+                Some((Ok(x), Ok(y))) => basics::StartPosition::At(basics::Position { x, y }),
+                _ => config_default.start,
+            },
+        }
+    } else {
+        config_default.start
+    };
+    let openings: basics::Sides = if tiles {
+        basics::Sides::parse(&ui::ask("- Openings in the outer wall [sides N, E, S, W; like WE](none): "))
+    } else {
+        config_default.openings
+    };
+    let mask: Option<basics::Mask> = if tiles {
+        match ui::ask("- Shape mask [circle/ heart/ diamond/ path of a PBM or text file](none): ").as_str() {
+            "" => None,
+            text => match basics::MaskShape::parse(text) {
+                Some(shape) => Some(basics::Mask::shape(shape, labyrinth_width, labyrinth_height)),
+                None => file_handler::read_mask(text),
+            },
+        }
+    } else {
+        None
+    };
+    // Probabilities: infinite and NaN inputs are rejected like any other unreadable input.
+    let (islet, weave): (basics::Islet, f32) = if walker {
+        let islet: basics::Islet = match ui::ask("- Islet probability [0 = No/ 0..1](default): ").parse::<f32>().ok().filter(|p| p.is_finite()) {
+            Some(p) if p <= 0f32 => basics::Islet::No,
+            Some(p) => basics::Islet::Yes(p.min(1f32)),
+            None => config_default.islet,
+        };
+        let weave: f32 = match ui::ask(&format!("- Weave, probability to cross under a corridor [0 = No/ 0..1]({}): ", config_default.weave)).parse::<f32>().ok().filter(|p| p.is_finite()) {
+            Some(p) => p.clamp(0f32, 1f32),
+            None => config_default.weave,
+        };
+        (islet, weave)
+    } else {
+        (config_default.islet, config_default.weave)
+    };
+    // Growing tree reads it as its cell selection, the hexagon walker gets stuck too.
+    let stuck_chosen: Option<basics::StuckReaction> = if walker || matches!(algorithm.name(), "growing_tree" | "hex_memory_based") {
+        match ui::ask("- Stuck reaction [OSB = One step back/ RP = Random position](default): ").to_uppercase().as_str() {
+            "OSB" => Some(basics::StuckReaction::OneStepBack),
+            "RP" => Some(basics::StuckReaction::RandomPosition),
+            _ => None,
+        }
+    } else {
+        None
+    };
+    let unsubordination: basics::Unsubordination = if walker {
+        match ui::ask("- Unsubordination, probability to ignore the pathing rules [0 = No/ 0..1](0): ").parse::<f32>().ok().filter(|p| p.is_finite()) {
+            Some(p) if p <= 0f32 => basics::Unsubordination::No,
+            Some(p) => basics::Unsubordination::Yes(p.min(1f32)),
+            None => config_default.unsubordination,
+        }
+    } else {
+        config_default.unsubordination
+    };
+    let stuck_reaction: basics::StuckReaction = stuck_chosen.unwrap_or(config_default.stuck_reaction);
+    let cell_selection: basics::CellSelection = if tiles && algorithm.name() == "growing_tree" {
+        // A stuck reaction entered stands for its policy; the default is the library's.
+        match basics::CellSelection::parse(&ui::ask("- Cell selection [newest/ oldest/ random/ middle/ mix like newest:75,random:25](stuck reaction entered, or newest): ")) {
+            Some(cell_selection) => cell_selection,
+            None => stuck_chosen.map(basics::CellSelection::from).unwrap_or(config_default.cell_selection),
+        }
+    } else {
+        config_default.cell_selection
+    };
+    let room_size: usize = if tiles && algorithm.name() == "recursive_division" {
+        match ui::ask(&format!("- Room size, chambers left open [N+ cells]({}): ", config_default.room_size)).parse() {
+            Ok(num) => num,
+            Err(_) => config_default.room_size,
+        }
+    } else {
+        config_default.room_size
+    };
+    let dungeon_rooms: usize = if tiles && algorithm.name() == "dungeon" {
+        match ui::ask(&format!("- Dungeon rooms, attempts to place one [N]({}): ", config_default.dungeon_rooms)).parse() {
+            Ok(num) => num,
+            Err(_) => config_default.dungeon_rooms,
+        }
+    } else {
+        config_default.dungeon_rooms
+    };
+    let (cave_rule, cave_fill, cave_steps): (basics::CaveRule, f32, usize) = if tiles && algorithm.name() == "cave" {
+        let cave_rule: basics::CaveRule = match basics::CaveRule::parse(&ui::ask("- Cave rule [Bx/Sy](B678/S345678): ")) {
+            Some(cave_rule) => cave_rule,
+            None => config_default.cave_rule,
+        };
+        let cave_fill: f32 = match ui::ask(&format!("- Cave fill, probability of a wall in the noise [0..1]({}): ", config_default.cave_fill)).parse::<f32>().ok().filter(|p| p.is_finite()) {
+            Some(p) => p.clamp(0f32, 1f32),
+            None => config_default.cave_fill,
+        };
+        let cave_steps: usize = match ui::ask(&format!("- Cave steps of the rule [N]({}): ", config_default.cave_steps)).parse() {
+            Ok(num) => num,
+            Err(_) => config_default.cave_steps,
+        };
+        (cave_rule, cave_fill, cave_steps)
+    } else {
+        (config_default.cave_rule, config_default.cave_fill, config_default.cave_steps)
+    };
+    let bias: basics::DirectionBias = if walker {
+        match basics::DirectionBias::parse(&ui::ask("- Direction weights N,E,S,W, then straight probability [like 4,1,4,1,0.5](1,1,1,1,0): ")) {
+            Some(bias) => bias,
+            None => config_default.bias,
+        }
+    } else {
+        config_default.bias
     };
+    let (levels, stairs): (usize, f32) = if algorithm.name() == "random_memory_based" {
+        let levels: usize = match ui::ask(&format!("- Levels, joined by stairs [N+]({}): ", config_default.levels)).parse() {
+            Ok(num) => num,
+            Err(_) => config_default.levels,
+        };
+        let stairs: f32 = if levels > 1 {
+            match ui::ask(&format!("- Stairs probability, at each step [0..1]({}): ", config_default.stairs)).parse::<f32>().ok().filter(|p| p.is_finite()) {
+                Some(p) => p.clamp(0f32, 1f32),
+                None => config_default.stairs,
+            }
+        } else {
+            config_default.stairs
+        };
+        (levels, stairs)
+    } else {
+        (config_default.levels, config_default.stairs)
+    };
+    let config: basics::GeneratorConfig = config_default
+        .width(labyrinth_width)
+        .height(labyrinth_height)
+        .iteration_limit(iteration_limit)
+        .seed(seed)
+        .start(start)
+        .openings(openings)
+        .mask(mask)
+        .islet(islet)
+        .weave(weave)
+        .stuck_reaction(stuck_reaction)
+        .unsubordination(unsubordination)
+        .bias(bias)
+        .cell_selection(cell_selection)
+        .room_size(room_size)
+        .cave_rule(cave_rule)
+        .cave_fill(cave_fill)
+        .cave_steps(cave_steps)
+        .dungeon_rooms(dungeon_rooms)
+        .levels(levels)
+        .stairs(stairs);
+
+    // Passes on the tile grid, after generation.
+    let (braid_fraction, exit_placement, sparseness_iterations): (f32, post_processing::ExitPlacement, usize) = if tiles {
+        let braid_fraction: f32 = match ui::ask("- Braid, fraction of dead ends removed [0..1](0): ").parse::<f32>().ok().filter(|fraction| fraction.is_finite()) {
+            Some(fraction) => fraction.clamp(0f32, 1f32),
+            None => 0f32,
+        };
+        let exit_placement: post_processing::ExitPlacement = match ui::ask("- Exit [N = None/ F = Farthest tile/ B = Border opening](F): ").to_uppercase().as_str() {
+            "N" => post_processing::ExitPlacement::None,
+            "B" => post_processing::ExitPlacement::Border,
+            _ => post_processing::ExitPlacement::Farthest,
+        };
+        let sparseness_iterations: usize = ui::ask("- Sparseness, dead end retraction passes [0 = No/ N+](0): ").parse().unwrap_or_default();
+        (braid_fraction, exit_placement, sparseness_iterations)
+    } else {
+        (0f32, post_processing::ExitPlacement::None, 0)
+    };
+    // Every setting, for the saved files: enough to make the same labyrinth again.
+    let settings: String = format!(
+        "{}- Braid: {}\n- Exit: {}\n- Sparseness: {}\n", 
+        config, braid_fraction, exit_placement, sparseness_iterations
+    );
+
+    let generator: Box<dyn generators::MazeGenerator> = match algorithm {
+        generators::Algorithm::Tiles(generator) => generator,
+        // Other topologies, not held in a tile grid: drawn as an SVG.
+        generators::Algorithm::Cells { name, topology: topology_of, .. } => {
+            let time_topology_start: Instant = Instant::now();
+            let topology: Box<dyn topology::Topology> = topology_of(&config);
+            let entrance: usize = topology.start(&config);
+            let passages: topology::Passages = topology::backtracker(topology.as_ref(), entrance, &config);
+            let (exit, exit_distance) = passages.farthest(entrance);
+            let svg: String = topology::to_svg(topology.as_ref(), &passages, entrance, exit, &format!("- Algorithm: {}\n{}", name, settings));
+            let time_topology_duration: Duration = time_topology_start.elapsed();
+
+            println!("\n## Results - Labyrinth: ");
+            if basics::DEFAULT_WRITE_TO_FILE {
+                file_handler::new_labyrinth_svg(svg);
+            } else {
+                println!("{}", svg);
+            }
+            println!("- Algorithm: {}", name);
+            println!("- Cells: {}; exit path length: {}", topology.len(), exit_distance);
+            println!("- Seed: {}", config.seed);
+            ui::dp(format!("- Generation time: {:?}\n", time_topology_duration), ui::DebugLogging::Minimal);
+            ui::wait_exit();
+            return;
+        },
+        // World, without edges: only a view around the origin is printed.
+        generators::Algorithm::World => {
+            let view: i32 = ui::ask("- View, chunks around the origin in each direction [N+](1): ").parse().unwrap_or(1);
+            let time_world_start: Instant = Instant::now();
+            let mut world: world::World = world::World::new(&config, basics::DEFAULT_WORLD_CACHE, &labyrinth_ui_features);
+            let chunk_size: i32 = world.chunk_size as i32;
+            let world_string: String = ui::world_to_string(
+                &mut world,
+                basics::Position { x: -view * chunk_size, y: -view * chunk_size },
+                basics::Position { x: (view + 1) * chunk_size - 1, y: (view + 1) * chunk_size - 1 },
+                &ui::LABYRINTH_UI_TILES
+            );
+            let time_world_duration: Duration = time_world_start.elapsed();
+
+            println!("\n## Results - Labyrinth: ");
+            println!("{}", world_string);
+            println!("- Algorithm: {}; chunks generated: {}, loaded: {}", world::NAME, world.generated, world.loaded());
+            println!("- Seed: {}", config.seed);
+            ui::dp(format!("- Generation time: {:?}\n", time_world_duration), ui::DebugLogging::Minimal);
+            if basics::DEFAULT_WRITE_TO_FILE {
+                file_handler::new_labyrinth(format!(
+                    "{}- Algorithm: {}\n{}- View: {}\n- Generation time: {:?}", 
+                    world_string, world::NAME, settings, view, time_world_duration
+                ));
+            }
+            ui::wait_exit();
+            return;
+        },
+    };
+
+    // Levels, printed one after another; the passes after generation are for a single level.
+    if config.levels > 1 {
+        if !config.openings.directions().is_empty() || braid_fraction > 0f32 || sparseness_iterations > 0 {
+            println!("(!) - Openings, braid and sparseness are for a single level, skipped.");
+        }
+        let time_levels_start: Instant = Instant::now();
+        let mut levels: generators::levels::Levels = generators::levels::levels_memory_based(&config, &labyrinth_ui_features);
+        let exit: Option<(usize, basics::Position, usize)> = levels.place_exit(&config, exit_placement);
+        let time_levels_duration: Duration = time_levels_start.elapsed();
+
+        println!("\n## Results - Labyrinth: ");
+        let mut labyrinth_string: String = String::new();
+        for (level, labyrinth) in levels.levels.iter().enumerate() {
+            labyrinth_string.push_str(&format!("### Level {}\n{}", level, ui::labyrinth_to_string(labyrinth, &ui::LABYRINTH_UI_TILES, &labyrinth_ui_features)));
+        }
+        println!("{}", labyrinth_string);
+        println!("- Algorithm: {}; levels: {}; stairs: {}", generator.name(), config.levels, levels.stairs.len());
+        if let Some((exit_level, exit_position, exit_distance)) = exit {
+            println!("- Exit: level {}, x={}, y={}; path length: {}", exit_level, exit_position.x, exit_position.y, exit_distance);
+        }
+        println!("- Seed: {}", config.seed);
+        ui::dp(format!("- Generation time: {:?}\n", time_levels_duration), ui::DebugLogging::Minimal);
+        if basics::DEFAULT_WRITE_TO_FILE {
+            file_handler::new_labyrinth(format!(
+                "{}- Algorithm: {}\n{}- Generation time: {:?}", 
+                labyrinth_string, generator.name(), settings, time_levels_duration
+            ));
+        }
+        ui::wait_exit();
+        return;
+    }
+
+    // Streaming, for row by row generators: rows go straight to a file, never held in a grid.
+    if generator.name() == "eller" {
+        let stream_height: usize = ui::ask("- Stream height, rows written straight to a file [0 = No/ N+](0): ").parse().unwrap_or_default();
+        if stream_height > 0 {
+            if config.mask.is_some() {
+                println!("(!) - Streamed rows are not held in a grid: the mask is ignored.");
+            }
+            if !config.openings.directions().is_empty() 
+                || braid_fraction > 0f32 
+                || exit_placement != post_processing::ExitPlacement::None 
+                || sparseness_iterations > 0 
+            {
+                println!("(!) - Openings, braid, exit and sparseness are for a labyrinth held in a grid, skipped.");
+            }
+            let lattice: generators::Lattice = generators::Lattice::of_size(config.width, stream_height);
+            let rows: generators::eller::EllerRows = generators::eller::EllerRows::new(lattice.width, lattice.height, config.seed);
+            let time_stream_start: Instant = Instant::now();
+            file_handler::new_labyrinth_stream(
+                format!(
+                    "- Max: x={}, y={}; Min: x=0, y=0;\n- Size: x={}, y={}\n- Legend: on={}, off={}\n- Algorithm: {} (stream)\n{}- Stream height: {}\n", 
+                    rows.tiles_width() - 1, lattice.height * 2, rows.tiles_width() - 1, lattice.height * 2, ui::LABYRINTH_UI_TILES.on, ui::LABYRINTH_UI_TILES.off, generator.name(), settings, stream_height
+                ),
+                rows.map(|row| generators::eller::row_to_string(&row))
+            );
+            ui::dp(format!("- Generation time: {:?}\n", time_stream_start.elapsed()), ui::DebugLogging::Minimal);
+            ui::wait_exit();
+            return;
+        }
+    }
 
     // Results
     let time_grmb_start: Instant = Instant::now();
-    let labyrinth: Grid = generators::random_memory_based(labyrinth_size, iteration_limit, &labyrinth_ui_features);
+    let mut labyrinth: generators::Labyrinth = generator.generate(&config, &labyrinth_ui_features);
+    let squares: bool = labyrinth.tessellation == basics::Tessellation::Squares;
+    if !squares && (braid_fraction > 0f32 || sparseness_iterations > 0) {
+        println!("(!) - Braid and sparseness are for square tiles only, skipped.");
+    }
+    if braid_fraction > 0f32 && squares {
+        let crossings: Vec<basics::Position> = post_processing::crossings(&labyrinth);
+        let (removed, asked) = post_processing::braid(&mut labyrinth.grid, &crossings, &config, braid_fraction);
+        if removed < asked {
+            println!("(!) - Braid: {} dead ends removed, out of {} asked; the ways left would remove more, or lead out of the labyrinth.", removed, asked);
+        }
+    }
+    let openings: Vec<basics::Position> = post_processing::open_borders(&mut labyrinth, &config, config.openings);
+    // The openings are the exits, when there are some.
+    let exit: Option<(basics::Position, usize)> = match openings.first() {
+        Some(opening) => {
+            if exit_placement != post_processing::ExitPlacement::None {
+                println!("(!) - The openings are the exits, no other placed.");
+            }
+            post_processing::path_length(&labyrinth, *opening).map(|distance| (*opening, distance))
+        },
+        None => post_processing::place_exit(&mut labyrinth, &config, exit_placement),
+    };
+    if sparseness_iterations > 0 && squares {
+        post_processing::sparsify(&mut labyrinth, &config, sparseness_iterations);
+    }
     let time_grmb_duration: Duration = time_grmb_start.elapsed();
 
     println!("\n## Results - Labyrinth: ");
     // labyrinth.display_inline(&LABYRINTH_UI_TILES, &labyrinth_ui_features);
-    let labyrinth_string: String = labyrinth.to_string(&ui::LABYRINTH_UI_TILES, &labyrinth_ui_features);
+    let labyrinth_string: String = ui::labyrinth_to_string(&labyrinth, &ui::LABYRINTH_UI_TILES, &labyrinth_ui_features);
     println!("{}", labyrinth_string);
+    println!("- Algorithm: {}", generator.name());
+    if squares {
+        println!("- Straightness: {:.2}", post_processing::straightness(&labyrinth.grid, &post_processing::crossings(&labyrinth)));
+    }
+    if let Some((exit_position, exit_distance)) = exit {
+        println!("- Exit: x={}, y={}; path length: {}", exit_position.x, exit_position.y, exit_distance);
+    }
+    println!("- Seed: {}", config.seed);
     ui::dp(format!("- Generation time: {:?}\n", time_grmb_duration), ui::DebugLogging::Minimal);
 
     // Log
     if basics::DEFAULT_WRITE_TO_FILE {
         file_handler::new_labyrinth(format!(
-            "{}- Generation time: {:?}", 
-            labyrinth_string, time_grmb_duration
+            "{}- Algorithm: {}\n{}- Generation time: {:?}", 
+            labyrinth_string, generator.name(), settings, time_grmb_duration
         ));
     }
 
-    // Prevent window of closing
-    println!("\nPress Enter to exit... ");
-    let _ = io::stdin().read_line(&mut String::new()).expect("(X) - Can't read line; closing anyway.");
+    ui::wait_exit();
 }
@@ -0,0 +1,561 @@
+// LABYRINTH
+// Post-processing passes, on any generated grid.
+
+use std::collections::VecDeque;
+use super::{seq::SliceRandom, SeedableRng, StdRng};
+use super::{basics, ui, grid, generators};
+
+/// Tile next to `tile`, `steps` away in the direction.
+fn step(tile: basics::Position, direction: basics::OrdinalDirections, steps: i32) -> basics::Position {
+    let offset: basics::Position = direction.offset();
+    basics::Position { x: tile.x + offset.x * steps, y: tile.y + offset.y * steps }
+}
+
+/// Tiles next to `tile`, according to the shape of the tiles.
+fn neighbours(tessellation: basics::Tessellation, tile: basics::Position) -> Vec<basics::Position> {
+    match tessellation {
+        basics::Tessellation::Squares => basics::OrdinalDirections::ALL.iter().map(|direction| step(tile, *direction, 1)).collect(),
+        basics::Tessellation::Hexagons => basics::HexDirections::ALL.iter().map(|direction| direction.step(tile)).collect(),
+    }
+}
+
+fn is_path(grid_labyrinth: &grid::Grid, tile: basics::Position) -> bool {
+    matches!(grid_labyrinth.state_tile(tile.x, tile.y), grid::TileState::On)
+}
+
+/// Directions in which the tile is joined to a path. The tiles of `crossings` are gone through straight:
+/// the tile joins one only if the tile beyond it is a path too, the passage being whole.
+fn links(grid_labyrinth: &grid::Grid, crossings: &[basics::Position], tile: basics::Position) -> Vec<basics::OrdinalDirections> {
+    basics::OrdinalDirections::ALL
+        .iter()
+        .copied()
+        .filter(|direction| {
+            let neighbour: basics::Position = step(tile, *direction, 1);
+            is_path(grid_labyrinth, neighbour) && (!crossings.contains(&neighbour) || is_path(grid_labyrinth, step(tile, *direction, 2)))
+        })
+        .collect()
+}
+
+/// Count of paths the tile is joined to, among its 4 neighbours; see `links` for the crossings.
+pub fn paths_around(grid_labyrinth: &grid::Grid, crossings: &[basics::Position], tile: basics::Position) -> usize {
+    links(grid_labyrinth, crossings, tile).len()
+}
+
+/// A path with a single path joined to it; crossings are never dead ends.
+pub fn is_dead_end(grid_labyrinth: &grid::Grid, crossings: &[basics::Position], tile: basics::Position) -> bool {
+    is_path(grid_labyrinth, tile) && !crossings.contains(&tile) && paths_around(grid_labyrinth, crossings, tile) == 1
+}
+
+/// Every dead end of the grid, row by row.
+pub fn dead_ends(grid_labyrinth: &grid::Grid, crossings: &[basics::Position]) -> Vec<basics::Position> {
+    let mut tiles: Vec<basics::Position> = Vec::new();
+    for y in 0..=grid_labyrinth.size.y as i32 {
+        for x in 0..=grid_labyrinth.size.x as i32 {
+            let tile: basics::Position = basics::Position { x, y };
+            if is_dead_end(grid_labyrinth, crossings, tile) {
+                tiles.push(tile);
+            }
+        }
+    }
+    tiles
+}
+
+/// Straightness of the corridors: among the path tiles joined to exactly two paths, the share going straight through.
+/// 1 for long straight runs, 0 for a path turning at every tile; 0 without corridor. Crossings are left out, always straight.
+pub fn straightness(grid_labyrinth: &grid::Grid, crossings: &[basics::Position]) -> f32 {
+    let mut corridors: usize = 0;
+    let mut straight: usize = 0;
+    for y in 0..=grid_labyrinth.size.y as i32 {
+        for x in 0..=grid_labyrinth.size.x as i32 {
+            let tile: basics::Position = basics::Position { x, y };
+            if !is_path(grid_labyrinth, tile) || crossings.contains(&tile) {
+                continue;
+            }
+            let tile_links: Vec<basics::OrdinalDirections> = links(grid_labyrinth, crossings, tile);
+            if tile_links.len() != 2 {
+                continue;
+            }
+            corridors += 1usize;
+            let (first, second) = (tile_links[0].offset(), tile_links[1].offset());
+            if first.x == -second.x && first.y == -second.y {
+                straight += 1usize;
+            }
+        }
+    }
+    if corridors == 0 { 0f32 } else { straight as f32 / corridors as f32 }
+}
+
+/// Walls to knock through from the dead end `tile`, straight in the direction, up to the first one next to a path;
+/// with the count of dead ends it removes, the tile included. `None` if the way leaves the labyrinth first.
+fn knock_through(
+    grid_labyrinth: &grid::Grid, 
+    crossings: &[basics::Position], 
+    config: &basics::GeneratorConfig, 
+    tile: basics::Position, 
+    direction: basics::OrdinalDirections
+) -> Option<(Vec<basics::Position>, usize)> {
+    let mut walls: Vec<basics::Position> = Vec::new();
+    let mut previous: basics::Position = tile;
+    loop {
+        let wall: basics::Position = step(previous, direction, 1);
+        if !matches!(generators::state_tile(grid_labyrinth, config, wall.x, wall.y), grid::TileState::Off) {
+            return None;
+        }
+        walls.push(wall);
+        let touching: Vec<basics::Position> = basics::OrdinalDirections::ALL
+            .iter()
+            .map(|around| step(wall, *around, 1))
+            .filter(|around| *around != previous && is_path(grid_labyrinth, *around))
+            .collect();
+        if !touching.is_empty() {
+            let joined: usize = 1 + touching.iter().filter(|around| is_dead_end(grid_labyrinth, crossings, **around)).count();
+            return Some((walls, joined));
+        }
+        previous = wall;
+    }
+}
+
+/// # Braid: remove a fraction of the dead ends, making loops.
+/// Crossings are passages, see `links`: a wall is never next to one, so only the dead ends depend on them.
+/// `fraction` of the dead ends, rounded, are knocked through to a path, in a random order: straight from the dead end,
+/// through the walls up to the first one next to a path. A knock through removes every dead end next to it.
+/// Among the ways of a dead end, the one removing the most is chosen, without going beyond the count, then the one through the fewest walls:
+/// joining two dead ends, through a single wall, keeps the most walls.
+/// Deterministic for a given seed. Gives the count of dead ends removed, and the count asked; short of it only when every way left would remove more
+/// than asked, like the last dead end of the count facing another dead end, or when a dead end only faces the border.
+pub fn braid(
+    grid_labyrinth: &mut grid::Grid, 
+    crossings: &[basics::Position], 
+    config: &basics::GeneratorConfig, 
+    fraction: f32
+) -> (usize, usize) {
+    let mut braid_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let mut tiles: Vec<basics::Position> = dead_ends(grid_labyrinth, crossings);
+    let count: usize = (fraction.clamp(0f32, 1f32) * tiles.len() as f32).round() as usize;
+    let mut removed: usize = 0;
+    tiles.shuffle(&mut braid_rng);
+
+    for tile in tiles {
+        if removed >= count {
+            break;
+        }
+        // Already joined by a previous knock through.
+        if !is_dead_end(grid_labyrinth, crossings, tile) {
+            continue;
+        }
+        let mut candidates: Vec<(Vec<basics::Position>, usize)> = basics::OrdinalDirections::ALL
+            .iter()
+            .filter_map(|direction| knock_through(grid_labyrinth, crossings, config, tile, *direction))
+            .filter(|(_, joined)| removed + joined <= count)
+            .collect();
+        candidates.shuffle(&mut braid_rng);
+        if let Some((walls, joined)) = candidates
+            .into_iter()
+            .max_by_key(|(walls, joined)| (*joined, std::cmp::Reverse(walls.len()))) 
+        {
+            for wall in walls {
+                grid_labyrinth.update_tile(wall.x, wall.y, !config.default_state, Vec::new());
+            }
+            removed += joined;
+        }
+    }
+    ui::dp(format!("- Braid: {} dead ends removed, out of {} asked.\n", removed, count), ui::DebugLogging::Minimal);
+
+    (removed, count)
+}
+
+/// # Sparseness: retract the dead ends, leaving solid areas.
+/// Fill every dead end back as a wall, `iterations` times; each pass shortens every dead-end corridor by one tile.
+/// The entrance and the exits are never filled; tags on filled tiles are dropped. Gives the count of tiles filled.
+/// Nor are the dead ends joined through a crossing: the passage under would lead nowhere, so both ways of a crossing stay whole.
+pub fn sparsify(
+    labyrinth: &mut generators::Labyrinth, 
+    config: &basics::GeneratorConfig, 
+    iterations: usize
+) -> usize {
+    let keep: Vec<basics::Position> = [vec![labyrinth.entrance], labyrinth.exits.clone()].concat();
+    let crossings: Vec<basics::Position> = crossings(labyrinth);
+    let mut filled: usize = 0;
+    for iteration in 0..iterations {
+        let tiles: Vec<basics::Position> = dead_ends(&labyrinth.grid, &crossings)
+            .into_iter()
+            .filter(|tile| !keep.contains(tile))
+            .filter(|tile| links(&labyrinth.grid, &crossings, *tile)
+                .iter()
+                .all(|direction| !crossings.contains(&step(*tile, *direction, 1)))
+            )
+            .collect();
+        if tiles.is_empty() {
+            ui::dp(format!("- Sparseness: no dead end left after {} passes.\n", iteration), ui::DebugLogging::Minimal);
+            break;
+        }
+        for tile in &tiles {
+            labyrinth.grid.update_tile(tile.x, tile.y, config.default_state, Vec::new());
+        }
+        filled += tiles.len();
+    }
+    let grid_labyrinth: &grid::Grid = &labyrinth.grid;
+    labyrinth.tags.retain(|(tile, _)| is_path(grid_labyrinth, *tile));
+    ui::dp(format!("- Sparseness: {} tiles filled.\n", filled), ui::DebugLogging::Minimal);
+
+    filled
+}
+
+/// Where to place the exit.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExitPlacement {
+    None,
+    /// Path tile the farthest from the entrance.
+    Farthest,
+    /// Opening in the outer wall, the farthest from the entrance.
+    Border,
+}
+
+/// Initial of the placement, as asked in the prompts.
+impl std::fmt::Display for ExitPlacement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            ExitPlacement::None => "N",
+            ExitPlacement::Farthest => "F",
+            ExitPlacement::Border => "B",
+        })
+    }
+}
+
+/// Breadth-first distances, in tiles, from `from` to every path tile reachable; `None` elsewhere.
+/// Indexed by `y * width + x`, `width` being the grid's `size.x + 1`.
+/// The tiles of `crossings` hold two passages, one over the other: they are only gone through straight, and have no distance.
+pub fn distances(
+    grid_labyrinth: &grid::Grid, 
+    tessellation: basics::Tessellation, 
+    crossings: &[basics::Position], 
+    from: basics::Position
+) -> Vec<Option<usize>> {
+    let width: usize = grid_labyrinth.size.x + 1;
+    let height: usize = grid_labyrinth.size.y + 1;
+    let mut tiles_distance: Vec<Option<usize>> = vec![None; width * height];
+    if !is_path(grid_labyrinth, from) {
+        return tiles_distance;
+    }
+    let index = |tile: basics::Position| tile.y as usize * width + tile.x as usize;
+    let mut crossing_tiles: Vec<bool> = vec![false; width * height];
+    for crossing in crossings {
+        crossing_tiles[index(*crossing)] = true;
+    }
+    // Axis of a step, 0 along x: the passage of a crossing tile taken.
+    let axis = |tile: basics::Position, neighbour: basics::Position| if neighbour.y == tile.y { 0usize } else { 1usize };
+    // Distances of the passages along each axis of the crossings.
+    let mut crossings_distance: Vec<[Option<usize>; 2]> = vec![[None; 2]; width * height];
+    let mut queue: VecDeque<(basics::Position, usize)> = VecDeque::from([(from, 0)]);
+    tiles_distance[index(from)] = Some(0);
+
+    while let Some((tile, tile_axis)) = queue.pop_front() {
+        let distance: usize = if crossing_tiles[index(tile)] {
+            crossings_distance[index(tile)][tile_axis]
+        } else {
+            tiles_distance[index(tile)]
+        }.expect("(!) - Queued tile without distance.");
+        for neighbour in neighbours(tessellation, tile) {
+            if !is_path(grid_labyrinth, neighbour) || (crossing_tiles[index(tile)] && axis(tile, neighbour) != tile_axis) {
+                continue;
+            }
+            let neighbour_axis: usize = axis(tile, neighbour);
+            let neighbour_distance: &mut Option<usize> = if crossing_tiles[index(neighbour)] {
+                &mut crossings_distance[index(neighbour)][neighbour_axis]
+            } else {
+                &mut tiles_distance[index(neighbour)]
+            };
+            if neighbour_distance.is_none() {
+                *neighbour_distance = Some(distance + 1);
+                queue.push_back((neighbour, neighbour_axis));
+            }
+        }
+    }
+    tiles_distance
+}
+
+/// Tiles of the labyrinth tagged `Named("Crossing")`.
+pub fn crossings(labyrinth: &generators::Labyrinth) -> Vec<basics::Position> {
+    labyrinth.tags
+        .iter()
+        .filter(|(_, feature)| *feature == grid::TileFeatures::Named("Crossing"))
+        .map(|(tile, _)| *tile)
+        .collect()
+}
+
+/// Length of the shortest path from the entrance to the tile, in steps; `None` if not reachable.
+pub fn path_length(labyrinth: &generators::Labyrinth, to: basics::Position) -> Option<usize> {
+    let width: usize = labyrinth.grid.size.x + 1;
+    if to.x < 0 || to.y < 0 || to.x as usize >= width || to.y as usize > labyrinth.grid.size.y {
+        return None;
+    }
+    distances(&labyrinth.grid, labyrinth.tessellation, &crossings(labyrinth), labyrinth.entrance)[to.y as usize * width + to.x as usize]
+}
+
+/// Walls between the path tile and the outside, straight in the direction, the outermost last: the outer wall, and the spare line a lattice leaves at even sizes.
+/// `None` if the outside is farther, or if a path runs along the way, which the opening would join too.
+pub fn border_walls(
+    labyrinth: &generators::Labyrinth, 
+    tile: basics::Position, 
+    direction: basics::OrdinalDirections
+) -> Option<Vec<basics::Position>> {
+    let sideways: [basics::OrdinalDirections; 2] = match direction {
+        basics::OrdinalDirections::North | basics::OrdinalDirections::South => [basics::OrdinalDirections::East, basics::OrdinalDirections::West],
+        basics::OrdinalDirections::East | basics::OrdinalDirections::West => [basics::OrdinalDirections::North, basics::OrdinalDirections::South],
+    };
+    let mut walls: Vec<basics::Position> = Vec::new();
+    let mut wall: basics::Position = step(tile, direction, 1);
+    while matches!(labyrinth.state_tile(wall.x, wall.y), grid::TileState::Off) {
+        if walls.len() == 2 || sideways.iter().any(|side| is_path(&labyrinth.grid, step(wall, *side, 1))) {
+            return None;
+        }
+        walls.push(wall);
+        wall = step(wall, direction, 1);
+    }
+    let outside: bool = matches!(labyrinth.state_tile(wall.x, wall.y), grid::TileState::Void);
+    (outside && !walls.is_empty()).then_some(walls)
+}
+
+/// # Exit: tag the tile the farthest from the entrance as `Named("Exit")`.
+/// With `ExitPlacement::Border`, the outer wall is opened next to the farthest tile touching it instead; see `border_walls`.
+/// When no path can be opened to the outside, like in small dungeons, the exit falls back to the farthest tile.
+/// The exit is added to `labyrinth.exits`. Gives the exit and the length of the shortest path to it, in steps.
+pub fn place_exit(
+    labyrinth: &mut generators::Labyrinth, 
+    config: &basics::GeneratorConfig, 
+    placement: ExitPlacement
+) -> Option<(basics::Position, usize)> {
+    let width: usize = labyrinth.grid.size.x + 1;
+    let tiles_distance: Vec<Option<usize>> = distances(&labyrinth.grid, labyrinth.tessellation, &crossings(labyrinth), labyrinth.entrance);
+    // Tiles to carve, the exit last, and its distance.
+    let mut farthest: Option<(Vec<basics::Position>, usize)> = None;
+
+    for (index, distance) in tiles_distance.iter().enumerate() {
+        let Some(distance) = *distance else {
+            continue;
+        };
+        let tile: basics::Position = basics::Position { x: (index % width) as i32, y: (index / width) as i32 };
+        let candidate: Option<Vec<basics::Position>> = match (placement, labyrinth.tessellation) {
+            (ExitPlacement::None, _) => None,
+            (ExitPlacement::Farthest, _) => Some(vec![tile]),
+            (ExitPlacement::Border, basics::Tessellation::Squares) => basics::OrdinalDirections::ALL
+                .iter()
+                .filter_map(|direction| border_walls(labyrinth, tile, *direction))
+                .min_by_key(|walls| walls.len()),
+            // A wall with only the outside behind it.
+            (ExitPlacement::Border, basics::Tessellation::Hexagons) => neighbours(labyrinth.tessellation, tile)
+                .into_iter()
+                .find(|wall| matches!(labyrinth.state_tile(wall.x, wall.y), grid::TileState::Off) 
+                    && neighbours(labyrinth.tessellation, *wall)
+                        .iter()
+                        .any(|outside| matches!(labyrinth.state_tile(outside.x, outside.y), grid::TileState::Void))
+                )
+                .map(|wall| vec![wall]),
+        };
+        if let Some(walls) = candidate {
+            // Through the nearest path next to the first wall: with hexagons, it may not be the tile.
+            let exit_distance: usize = match placement {
+                ExitPlacement::Farthest => distance,
+                _ => neighbours(labyrinth.tessellation, walls[0])
+                    .iter()
+                    .filter(|neighbour| neighbour.x >= 0 && neighbour.y >= 0 && (neighbour.x as usize) < width)
+                    .filter_map(|neighbour| tiles_distance.get(neighbour.y as usize * width + neighbour.x as usize).copied().flatten())
+                    .min()
+                    .unwrap_or(distance) + walls.len(),
+            };
+            if farthest.as_ref().is_none_or(|(_, farthest_distance)| exit_distance > *farthest_distance) {
+                farthest = Some((walls, exit_distance));
+            }
+        }
+    }
+
+    let Some((tiles, exit_distance)) = farthest else {
+        if placement == ExitPlacement::Border {
+            println!("(!) - No path can be opened to the outside: exit at the farthest tile instead.");
+            return place_exit(labyrinth, config, ExitPlacement::Farthest);
+        }
+        return None;
+    };
+    let exit: basics::Position = *tiles.last()?;
+    for tile in &tiles {
+        let features: Vec<grid::TileFeatures> = if *tile == exit { vec![grid::TileFeatures::Named("Exit")] } else { Vec::new() };
+        labyrinth.grid.update_tile(tile.x, tile.y, !config.default_state, features);
+    }
+    labyrinth.exits.push(exit);
+    ui::dp(format!("- Exit: x={}, y={}; path length: {}.\n", exit.x, exit.y, exit_distance), ui::DebugLogging::Minimal);
+    Some((exit, exit_distance))
+}
+
+/// # Openings in the outer wall, one on each chosen side, and one on the side of the start if it is one.
+/// Each opening is in the middle of its side, see `generators::side_middle`, so that labyrinths of the same size line up when tiled edge to edge:
+/// from there, walls are carved inward until a path is met, past the outside of the mask if any.
+/// The opening on the side of the start becomes the entrance; the others are tagged `Named("Exit")` and added to `labyrinth.exits`. Gives the exits made.
+pub fn open_borders(
+    labyrinth: &mut generators::Labyrinth, 
+    config: &basics::GeneratorConfig, 
+    sides: basics::Sides
+) -> Vec<basics::Position> {
+    let tiles_max: basics::Position = labyrinth.tiles_max();
+    let start_side: Option<basics::OrdinalDirections> = match config.start {
+        basics::StartPosition::Side(side) => Some(side),
+        _ => None,
+    };
+    let mut openings: Vec<basics::Position> = Vec::new();
+
+    for side in basics::OrdinalDirections::ALL {
+        let entrance: bool = start_side == Some(side);
+        if !entrance && !sides.directions().contains(&side) {
+            continue;
+        }
+        let inward: basics::Position = {
+            let outward: basics::Position = side.offset();
+            basics::Position { x: -outward.x, y: -outward.y }
+        };
+        let inside = |tile: basics::Position| tile.x >= 0 && tile.y >= 0 && tile.x <= tiles_max.x && tile.y <= tiles_max.y;
+        let mut tile: basics::Position = generators::side_middle(tiles_max, side);
+        // Outside of the mask.
+        while inside(tile) && matches!(labyrinth.state_tile(tile.x, tile.y), grid::TileState::Void) {
+            tile = basics::Position { x: tile.x + inward.x, y: tile.y + inward.y };
+        }
+        // Walls up to the first one touching a path, the way out excluded.
+        let mut walls: Vec<basics::Position> = Vec::new();
+        let mut joined: bool = false;
+        while !joined && matches!(labyrinth.state_tile(tile.x, tile.y), grid::TileState::Off) {
+            let outer: basics::Position = basics::Position { x: tile.x - inward.x, y: tile.y - inward.y };
+            joined = neighbours(labyrinth.tessellation, tile)
+                .iter()
+                .any(|neighbour| *neighbour != outer && is_path(&labyrinth.grid, *neighbour));
+            walls.push(tile);
+            tile = basics::Position { x: tile.x + inward.x, y: tile.y + inward.y };
+        }
+        if !joined {
+            ui::dp(String::from("- No path to open a side toward.\n"), ui::DebugLogging::Minimal);
+            continue;
+        }
+
+        let opening: basics::Position = walls[0];
+        for wall in &walls {
+            labyrinth.grid.update_tile(wall.x, wall.y, !config.default_state, Vec::new());
+        }
+        if entrance {
+            labyrinth.grid.update_tile(opening.x, opening.y, !config.default_state, vec![grid::TileFeatures::Named("Entrance")]);
+            labyrinth.entrance = opening;
+        } else {
+            labyrinth.grid.update_tile(opening.x, opening.y, !config.default_state, vec![grid::TileFeatures::Named("Exit")]);
+            labyrinth.exits.push(opening);
+            openings.push(opening);
+        }
+    }
+    openings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Labyrinth of the registered generator, 21 by 13 tiles.
+    fn labyrinth_test(name: &str, seed: u64) -> (generators::Labyrinth, basics::GeneratorConfig) {
+        let config: basics::GeneratorConfig = basics::GeneratorConfig::new().width(21).height(13).seed(seed);
+        let Some(generators::Algorithm::Tiles(generator)) = generators::find(name) else {
+            panic!("{} is not a tile generator", name);
+        };
+        (generator.generate(&config, &HashMap::new()), config)
+    }
+
+    #[test]
+    fn braid_removes_rounded_fraction() {
+        for name in ["random_memory_based", "backtracker", "kruskal", "cave", "dungeon"] {
+            for seed in 0..8u64 {
+                for fraction in [0.3f32, 0.5f32, 1f32] {
+                    let (mut labyrinth, config) = labyrinth_test(name, seed);
+                    let crossings: Vec<basics::Position> = crossings(&labyrinth);
+                    let before: usize = dead_ends(&labyrinth.grid, &crossings).len();
+                    let (removed, asked) = braid(&mut labyrinth.grid, &crossings, &config, fraction);
+                    assert_eq!(asked, (fraction * before as f32).round() as usize);
+                    assert_eq!(removed, asked, "{} seed {} fraction {}", name, seed, fraction);
+                    assert_eq!(dead_ends(&labyrinth.grid, &crossings).len(), before - removed, "{} seed {} fraction {}", name, seed, fraction);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exit_distance_is_path_length() {
+        for name in ["random_memory_based", "backtracker", "eller", "cave", "dungeon", "hex_memory_based"] {
+            for seed in 0..8u64 {
+                for placement in [ExitPlacement::Farthest, ExitPlacement::Border] {
+                    let (mut labyrinth, config) = labyrinth_test(name, seed);
+                    let Some((exit, distance)) = place_exit(&mut labyrinth, &config, placement) else {
+                        continue;
+                    };
+                    assert!(labyrinth.exits == vec![exit]);
+                    assert_eq!(path_length(&labyrinth, exit), Some(distance), "{} seed {} exit {}", name, seed, placement);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn border_exit_falls_back_to_farthest() {
+        for seed in 0..8u64 {
+            let config: basics::GeneratorConfig = basics::GeneratorConfig::new().width(9).height(9).seed(seed);
+            let Some(generators::Algorithm::Tiles(generator)) = generators::find("dungeon") else {
+                panic!("dungeon is not a tile generator");
+            };
+            let mut labyrinth: generators::Labyrinth = generator.generate(&config, &HashMap::new());
+            assert!(place_exit(&mut labyrinth, &config, ExitPlacement::Border).is_some(), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn open_borders_one_per_side() {
+        for name in ["random_memory_based", "backtracker", "eller", "cave", "dungeon"] {
+            for seed in 0..8u64 {
+                let (mut labyrinth, config) = labyrinth_test(name, seed);
+                // The side of the start is the entrance, the others exits.
+                let config: basics::GeneratorConfig = config.start(basics::StartPosition::Side(basics::OrdinalDirections::West));
+                let exits: Vec<basics::Position> = open_borders(&mut labyrinth, &config, basics::Sides::parse("NES"));
+                assert_eq!(exits.len(), 3, "{} seed {}", name, seed);
+                assert!(labyrinth.exits == exits);
+                assert_eq!(labyrinth.entrance.x, 0, "{} seed {}", name, seed);
+                let tiles_max: basics::Position = labyrinth.tiles_max();
+                // Outer wall of each side: North, East, South, West.
+                let sides: [Vec<basics::Position>; 4] = [
+                    (0..=tiles_max.x).map(|x| basics::Position { x, y: tiles_max.y }).collect(),
+                    (0..=tiles_max.y).map(|y| basics::Position { x: tiles_max.x, y }).collect(),
+                    (0..=tiles_max.x).map(|x| basics::Position { x, y: 0 }).collect(),
+                    (0..=tiles_max.y).map(|y| basics::Position { x: 0, y }).collect(),
+                ];
+                for (side, tiles) in sides.iter().enumerate() {
+                    let opened: Vec<&basics::Position> = tiles.iter().filter(|tile| is_path(&labyrinth.grid, **tile)).collect();
+                    assert_eq!(opened.len(), 1, "{} seed {} side {}", name, seed, side);
+                    assert!(opened[0] == &labyrinth.entrance || exits.contains(opened[0]));
+                    assert!(path_length(&labyrinth, *opened[0]).is_some(), "{} seed {} side {}", name, seed, side);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sparsify_keeps_entrance_and_exits() {
+        for name in ["random_memory_based", "backtracker", "cave", "dungeon"] {
+            for seed in 0..8u64 {
+                let (mut labyrinth, config) = labyrinth_test(name, seed);
+                let Some((exit, distance)) = place_exit(&mut labyrinth, &config, ExitPlacement::Farthest) else {
+                    panic!("{} seed {}: no exit", name, seed);
+                };
+                sparsify(&mut labyrinth, &config, 1000);
+                for tile in [labyrinth.entrance, exit] {
+                    assert!(is_path(&labyrinth.grid, tile), "{} seed {}: x={}, y={} filled", name, seed, tile.x, tile.y);
+                }
+                assert_eq!(path_length(&labyrinth, exit), Some(distance), "{} seed {}", name, seed);
+                // A perfect maze is left with the way from the entrance to the exit only.
+                if name == "backtracker" {
+                    let paths: usize = (0..13).map(|y| (0..21).filter(|x| is_path(&labyrinth.grid, basics::Position { x: *x, y })).count()).sum();
+                    assert_eq!(paths, distance + 1, "seed {}", seed);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,434 @@
+// LABYRINTH
+// Topologies - Cell mazes beyond the tile grid: triangles (delta) and rings (theta)
+
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use super::{seq::IndexedRandom, SeedableRng, StdRng};
+use super::{basics, ui, generators};
+
+/// Size of a cell in the SVG, in pixels.
+const SVG_CELL: f32 = 20f32;
+/// Margin around the labyrinth in the SVG, in pixels.
+const SVG_MARGIN: f32 = 10f32;
+
+/// Wall between two cells, or on the outside, in SVG cords.
+#[derive(Clone, Copy)]
+pub enum Wall {
+    Line { from: (f32, f32), to: (f32, f32) },
+    /// Counterclockwise arc around `centre`.
+    Arc { centre: (f32, f32), radius: f32, from: f32, to: f32 },
+}
+
+/// # Cells and their neighbours, with the wall shared with each.
+/// Cells are indexed `0..len()`; the tile grid's `Position` cannot hold them.
+pub trait Topology {
+    fn name(&self) -> &'static str;
+    fn len(&self) -> usize;
+    /// Sides of the cell: the neighbour across it, `None` for the outer wall, and the wall.
+    fn sides(&self, cell: usize) -> Vec<(Option<usize>, Wall)>;
+    /// Centre of the cell in the SVG, for the entrance and exit marks.
+    fn centre(&self, cell: usize) -> (f32, f32);
+    /// Width and height of the SVG.
+    fn svg_size(&self) -> (f32, f32);
+    /// Cell where the generator starts, the entrance, according to `config.start`.
+    fn start(&self, config: &basics::GeneratorConfig) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn neighbours(&self, cell: usize) -> Vec<usize> {
+        self.sides(cell).into_iter().filter_map(|(neighbour, _)| neighbour).collect()
+    }
+}
+
+/// # Triangles in rows, alternately pointing up and down; delta labyrinth.
+/// Cell (x, y) points up when x + y is even, y pointing North.
+pub struct Delta {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Delta {
+    fn index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    fn pointing_up(x: usize, y: usize) -> bool {
+        (x + y).is_multiple_of(2)
+    }
+
+    /// Corners of the cell in the SVG: apex, then the two ends of the base, West first.
+    fn corners(&self, x: usize, y: usize) -> [(f32, f32); 3] {
+        let triangle_height: f32 = SVG_CELL * 3f32.sqrt() / 2f32;
+        let left: f32 = SVG_MARGIN + x as f32 * SVG_CELL / 2f32;
+        let top: f32 = SVG_MARGIN + (self.height - 1 - y) as f32 * triangle_height;
+        if Delta::pointing_up(x, y) {
+            [(left + SVG_CELL / 2f32, top), (left, top + triangle_height), (left + SVG_CELL, top + triangle_height)]
+        } else {
+            [(left + SVG_CELL / 2f32, top + triangle_height), (left, top), (left + SVG_CELL, top)]
+        }
+    }
+}
+
+impl Topology for Delta {
+    fn name(&self) -> &'static str {
+        "delta"
+    }
+
+    fn len(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn sides(&self, cell: usize) -> Vec<(Option<usize>, Wall)> {
+        let (x, y) = (cell % self.width, cell / self.width);
+        let [apex, base_west, base_east] = self.corners(x, y);
+        let base: Option<usize> = if Delta::pointing_up(x, y) {
+            y.checked_sub(1).map(|below| self.index(x, below))
+        } else {
+            Some(y + 1).filter(|above| *above < self.height).map(|above| self.index(x, above))
+        };
+        vec![
+            (x.checked_sub(1).map(|west| self.index(west, y)), Wall::Line { from: apex, to: base_west }),
+            (Some(x + 1).filter(|east| *east < self.width).map(|east| self.index(east, y)), Wall::Line { from: apex, to: base_east }),
+            (base, Wall::Line { from: base_west, to: base_east }),
+        ]
+    }
+
+    fn centre(&self, cell: usize) -> (f32, f32) {
+        let corners: [(f32, f32); 3] = self.corners(cell % self.width, cell / self.width);
+        (
+            corners.iter().map(|corner| corner.0).sum::<f32>() / 3f32,
+            corners.iter().map(|corner| corner.1).sum::<f32>() / 3f32,
+        )
+    }
+
+    fn svg_size(&self) -> (f32, f32) {
+        (
+            SVG_MARGIN * 2f32 + (self.width + 1) as f32 * SVG_CELL / 2f32,
+            SVG_MARGIN * 2f32 + self.height as f32 * SVG_CELL * 3f32.sqrt() / 2f32,
+        )
+    }
+
+    /// The start tile of the config, as a triangle of the rows.
+    fn start(&self, config: &basics::GeneratorConfig) -> usize {
+        let tile: basics::Position = generators::start_tile(config);
+        self.index(
+            (tile.x.max(0) as usize).min(self.width - 1), 
+            (tile.y.max(0) as usize).min(self.height - 1)
+        )
+    }
+}
+
+/// # Concentric rings around a centre cell; theta labyrinth.
+/// Outer rings subdivide their cells, so that cells keep about the same width.
+pub struct Theta {
+    /// Cells in each ring, the centre being the ring 0.
+    pub rings: Vec<usize>,
+    /// Index of the first cell of each ring.
+    firsts: Vec<usize>,
+}
+
+impl Theta {
+    pub fn new(rings: usize) -> Theta {
+        let rings_count: usize = rings.max(1);
+        let mut counts: Vec<usize> = vec![1];
+        for ring in 1..rings_count {
+            // Width the cells would have keeping the count of the ring inward, in ring heights.
+            let previous: usize = counts[ring - 1];
+            let width_estimated: f32 = 2f32 * PI * ring as f32 / previous as f32;
+            counts.push(previous * (width_estimated.round() as usize).max(1));
+        }
+        let firsts: Vec<usize> = counts
+            .iter()
+            .scan(0usize, |first, count| {
+                let current: usize = *first;
+                *first += count;
+                Some(current)
+            })
+            .collect();
+        Theta { rings: counts, firsts }
+    }
+
+    /// Ring and position in the ring of the cell.
+    fn locate(&self, cell: usize) -> (usize, usize) {
+        let ring: usize = self.firsts.iter().rposition(|first| *first <= cell).unwrap_or(0);
+        (ring, cell - self.firsts[ring])
+    }
+
+    fn svg_centre(&self) -> (f32, f32) {
+        let radius: f32 = self.rings.len() as f32 * SVG_CELL;
+        (SVG_MARGIN + radius, SVG_MARGIN + radius)
+    }
+
+    /// Angles covered by the cell, counterclockwise.
+    fn angles(&self, ring: usize, position: usize) -> (f32, f32) {
+        let width: f32 = 2f32 * PI / self.rings[ring] as f32;
+        (position as f32 * width, (position + 1) as f32 * width)
+    }
+
+    fn point(&self, radius: f32, angle: f32) -> (f32, f32) {
+        let centre: (f32, f32) = self.svg_centre();
+        (centre.0 + radius * angle.cos(), centre.1 - radius * angle.sin())
+    }
+}
+
+impl Topology for Theta {
+    fn name(&self) -> &'static str {
+        "theta"
+    }
+
+    fn len(&self) -> usize {
+        self.rings.iter().sum()
+    }
+
+    fn sides(&self, cell: usize) -> Vec<(Option<usize>, Wall)> {
+        let (ring, position) = self.locate(cell);
+        let count: usize = self.rings[ring];
+        let (angle_from, angle_to) = self.angles(ring, position);
+        let radius_inner: f32 = ring as f32 * SVG_CELL;
+        let radius_outer: f32 = (ring + 1) as f32 * SVG_CELL;
+        let mut sides: Vec<(Option<usize>, Wall)> = Vec::new();
+
+        if ring > 0 {
+            let parent: usize = self.firsts[ring - 1] + position / (count / self.rings[ring - 1]);
+            sides.push((Some(parent), Wall::Arc { centre: self.svg_centre(), radius: radius_inner, from: angle_from, to: angle_to }));
+            if count > 1 {
+                let clockwise: usize = self.firsts[ring] + (position + count - 1) % count;
+                let counterclockwise: usize = self.firsts[ring] + (position + 1) % count;
+                sides.push((Some(clockwise), Wall::Line { from: self.point(radius_inner, angle_from), to: self.point(radius_outer, angle_from) }));
+                sides.push((Some(counterclockwise), Wall::Line { from: self.point(radius_inner, angle_to), to: self.point(radius_outer, angle_to) }));
+            }
+        }
+        if ring + 1 < self.rings.len() {
+            let ratio: usize = self.rings[ring + 1] / count;
+            for child_position in position * ratio..(position + 1) * ratio {
+                let (child_from, child_to) = self.angles(ring + 1, child_position);
+                sides.push((
+                    Some(self.firsts[ring + 1] + child_position),
+                    Wall::Arc { centre: self.svg_centre(), radius: radius_outer, from: child_from, to: child_to },
+                ));
+            }
+        } else if count == 1 {
+            // A whole circle ends where it starts, and draws nothing: two halves.
+            for (half_from, half_to) in [(angle_from, PI), (PI, angle_to)] {
+                sides.push((None, Wall::Arc { centre: self.svg_centre(), radius: radius_outer, from: half_from, to: half_to }));
+            }
+        } else {
+            sides.push((None, Wall::Arc { centre: self.svg_centre(), radius: radius_outer, from: angle_from, to: angle_to }));
+        }
+        sides
+    }
+
+    fn centre(&self, cell: usize) -> (f32, f32) {
+        let (ring, position) = self.locate(cell);
+        if ring == 0 {
+            return self.svg_centre();
+        }
+        let (angle_from, angle_to) = self.angles(ring, position);
+        self.point((ring as f32 + 0.5f32) * SVG_CELL, (angle_from + angle_to) / 2f32)
+    }
+
+    fn svg_size(&self) -> (f32, f32) {
+        let side: f32 = SVG_MARGIN * 2f32 + self.rings.len() as f32 * SVG_CELL * 2f32;
+        (side, side)
+    }
+
+    /// Always the centre cell.
+    fn start(&self, _config: &basics::GeneratorConfig) -> usize {
+        0
+    }
+}
+
+/// Delta topology of the config: `width` by `height` triangles.
+pub fn delta(config: &basics::GeneratorConfig) -> Box<dyn Topology> {
+    Box::new(Delta { width: config.width.max(1), height: config.height.max(1) })
+}
+
+/// Theta topology of the config: half the smallest side in rings.
+pub fn theta(config: &basics::GeneratorConfig) -> Box<dyn Topology> {
+    Box::new(Theta::new(config.width.min(config.height) / 2))
+}
+
+/// Passages carved between cells.
+pub struct Passages {
+    links: Vec<Vec<usize>>,
+}
+
+impl Passages {
+    pub fn new(len: usize) -> Self {
+        Passages { links: vec![Vec::new(); len] }
+    }
+
+    pub fn link(&mut self, a: usize, b: usize) {
+        self.links[a].push(b);
+        self.links[b].push(a);
+    }
+
+    pub fn linked(&self, a: usize, b: usize) -> bool {
+        self.links[a].contains(&b)
+    }
+
+    /// Cell the farthest from `from` through the passages, and its distance in steps.
+    pub fn farthest(&self, from: usize) -> (usize, usize) {
+        let mut distances: Vec<Option<usize>> = vec![None; self.links.len()];
+        let mut queue: VecDeque<usize> = VecDeque::from([from]);
+        let mut farthest: (usize, usize) = (from, 0);
+        distances[from] = Some(0);
+        while let Some(cell) = queue.pop_front() {
+            let distance: usize = distances[cell].expect("(!) - Queued cell without distance.");
+            if distance > farthest.1 {
+                farthest = (cell, distance);
+            }
+            for neighbour in &self.links[cell] {
+                if distances[*neighbour].is_none() {
+                    distances[*neighbour] = Some(distance + 1);
+                    queue.push_back(*neighbour);
+                }
+            }
+        }
+        farthest
+    }
+}
+
+/// # Labyrinth generator; depth-first backtracker on any topology.
+/// Same as `recursive_backtracker`, on cells and their neighbours instead of the lattice.
+/// Iteration limit to zero to disable the limit.
+pub fn backtracker(topology: &dyn Topology, start: usize, config: &basics::GeneratorConfig) -> Passages {
+    ui::dp(format!("## Generating labyrinth, {} (seed {}).\n", topology.name(), config.seed), ui::DebugLogging::Minimal);
+    let mut generator_rng: StdRng = StdRng::seed_from_u64(config.seed);
+    let mut passages: Passages = Passages::new(topology.len());
+    if topology.is_empty() {
+        return passages;
+    }
+    let mut visited: Vec<bool> = vec![false; topology.len()];
+    let mut generator_stack: Vec<usize> = vec![start];
+    let mut counter: usize = 0;
+    visited[start] = true;
+
+    while let Some(&cell) = generator_stack.last()
+        && (config.iteration_limit < 1 || counter < config.iteration_limit)
+    {
+        counter += 1usize;
+        let unvisited: Vec<usize> = topology
+            .neighbours(cell)
+            .into_iter()
+            .filter(|neighbour| !visited[*neighbour])
+            .collect();
+        match unvisited.choose(&mut generator_rng) {
+            Some(&next) => {
+                passages.link(cell, next);
+                visited[next] = true;
+                generator_stack.push(next);
+            },
+            None => {
+                generator_stack.pop();
+            },
+        }
+    }
+    if config.iteration_limit >= 1 && counter >= config.iteration_limit {
+        ui::dp(format!("- Iteration limit reached ({}).\n", config.iteration_limit), ui::DebugLogging::Minimal);
+    }
+    passages
+}
+
+/// # SVG of the labyrinth: every wall not carved, and a mark on the entrance and the exit.
+/// A wall between two cells is drawn once, from the cell of the smallest index.
+/// `description` goes in the `<desc>` element, like the settings the labyrinth was made with.
+pub fn to_svg(topology: &dyn Topology, passages: &Passages, entrance: usize, exit: usize, description: &str) -> String {
+    let (width, height) = topology.svg_size();
+    let description: String = description.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let mut svg: String = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" viewBox=\"0 0 {:.1} {:.1}\">\n<desc>\n{}</desc>\n<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n<path fill=\"none\" stroke=\"black\" stroke-width=\"2\" stroke-linecap=\"round\" d=\"",
+        width, height, width, height, description
+    );
+    for cell in 0..topology.len() {
+        for (neighbour, wall) in topology.sides(cell) {
+            let drawn: bool = match neighbour {
+                Some(neighbour) => cell < neighbour && !passages.linked(cell, neighbour),
+                None => true,
+            };
+            if !drawn {
+                continue;
+            }
+            match wall {
+                Wall::Line { from, to } => svg.push_str(&format!("M{:.1} {:.1}L{:.1} {:.1}", from.0, from.1, to.0, to.1)),
+                Wall::Arc { centre, radius, from, to } => svg.push_str(&format!(
+                    "M{:.1} {:.1}A{:.1} {:.1} 0 0 0 {:.1} {:.1}",
+                    centre.0 + radius * from.cos(), centre.1 - radius * from.sin(),
+                    radius, radius,
+                    centre.0 + radius * to.cos(), centre.1 - radius * to.sin(),
+                )),
+            }
+        }
+    }
+    svg.push_str("\"/>\n");
+    for (cell, colour) in [(entrance, "green"), (exit, "red")] {
+        let (x, y) = topology.centre(cell);
+        svg.push_str(&format!("<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\"/>\n", x, y, SVG_CELL / 5f32, colour));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topologies() -> Vec<Box<dyn Topology>> {
+        let mut topologies: Vec<Box<dyn Topology>> = Vec::new();
+        for (width, height) in [(1usize, 1usize), (2, 1), (7, 5), (12, 9)] {
+            topologies.push(delta(&basics::GeneratorConfig::new().width(width).height(height)));
+        }
+        for rings in [1usize, 2, 3, 6] {
+            topologies.push(Box::new(Theta::new(rings)));
+        }
+        topologies
+    }
+
+    #[test]
+    fn neighbours_symmetric() {
+        for topology in topologies() {
+            for cell in 0..topology.len() {
+                for neighbour in topology.neighbours(cell) {
+                    assert!(neighbour < topology.len());
+                    assert!(topology.neighbours(neighbour).contains(&cell), "{} {} and {} of {}", topology.name(), cell, neighbour, topology.len());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn backtracker_spanning_tree() {
+        for topology in topologies() {
+            for seed in 0..8u64 {
+                let config: basics::GeneratorConfig = basics::GeneratorConfig::new().seed(seed);
+                let start: usize = topology.start(&config);
+                let passages: Passages = backtracker(topology.as_ref(), start, &config);
+                let links: usize = passages.links.iter().map(|links| links.len()).sum::<usize>() / 2;
+                assert_eq!(links, topology.len() - 1, "{} of {}, seed {}", topology.name(), topology.len(), seed);
+                let mut reached: Vec<bool> = vec![false; topology.len()];
+                let mut stack: Vec<usize> = vec![start];
+                reached[start] = true;
+                while let Some(cell) = stack.pop() {
+                    for neighbour in &passages.links[cell] {
+                        assert!(topology.neighbours(cell).contains(neighbour));
+                        if !reached[*neighbour] {
+                            reached[*neighbour] = true;
+                            stack.push(*neighbour);
+                        }
+                    }
+                }
+                assert!(reached.iter().all(|r| *r), "{} of {}, seed {}", topology.name(), topology.len(), seed);
+            }
+        }
+    }
+
+    #[test]
+    fn svg_holds_description() {
+        let topology: Box<dyn Topology> = delta(&basics::GeneratorConfig::new().width(4).height(3));
+        let passages: Passages = backtracker(topology.as_ref(), 0, &basics::GeneratorConfig::new());
+        let svg: String = to_svg(topology.as_ref(), &passages, 0, 1, "- Width: 4\n- Mask: <&>\n");
+        assert!(svg.contains("<desc>\n- Width: 4\n- Mask: &lt;&amp;&gt;\n</desc>"));
+    }
+}
@@ -5,7 +5,8 @@
 // cf. vars defs.
 
 
-use super::grid;
+use std::io;
+use super::{HashMap, basics, generators, grid, world};
 
 /// UI - Visualisation of the status of the tiles.
 pub const LABYRINTH_UI_TILES: grid::UiTiles = grid::UiTiles {
@@ -16,6 +17,77 @@ pub const LABYRINTH_UI_TILES: grid::UiTiles = grid::UiTiles {
 
 
 
+/// Text of the labyrinth, only its `width` by `height` tiles, North on top. 
+/// Tagged path tiles (entrance, exits, tags) show the glyph of their feature, if it has one; a tag on a wall is not drawn.
+/// Hexagonal tiles are drawn with their odd rows shifted by half a tile.
+pub fn labyrinth_to_string(
+    labyrinth: &generators::Labyrinth,
+    ui_tiles: &grid::UiTiles,
+    labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+) -> String {
+    let mut glyphs: HashMap<basics::Position, &'static str> = HashMap::new();
+    // Tags over the entrance: the entrance of an upper level is the stairs reaching it.
+    let tagged = std::iter::once((labyrinth.entrance, grid::TileFeatures::Named("Entrance")))
+        .chain(labyrinth.tags.iter().map(|(tile, feature)| (*tile, feature.clone())))
+        .chain(labyrinth.exits.iter().map(|exit| (*exit, grid::TileFeatures::Named("Exit"))));
+    for (tile, feature) in tagged {
+        if let Some(glyph) = labyrinth_ui_features.get(&feature)
+            && matches!(labyrinth.state_tile(tile.x, tile.y), grid::TileState::On)
+        {
+            glyphs.insert(tile, glyph);
+        }
+    }
+
+    // Same header as `grid::Grid::to_string`: sizes as the largest tile cords.
+    let tiles_max: basics::Position = labyrinth.tiles_max();
+    let mut text: String = format!(
+        "- Max: x={}, y={}; Min: x=0, y=0;\n- Size: x={}, y={}; Center: x={}, y={}\n- Legend: on={}, off={}, void={}\n",
+        tiles_max.x, tiles_max.y, tiles_max.x, tiles_max.y, tiles_max.x / 2, tiles_max.y / 2, ui_tiles.on, ui_tiles.off, ui_tiles.void
+    );
+    for y in (0..labyrinth.height as i32).rev() {
+        if labyrinth.tessellation == basics::Tessellation::Hexagons && y % 2 == 1 {
+            text.push(' ');
+        }
+        for x in 0..labyrinth.width as i32 {
+            let glyph: &str = match glyphs.get(&basics::Position { x, y }) {
+                Some(glyph) => glyph,
+                None => match labyrinth.state_tile(x, y) {
+                    grid::TileState::On => ui_tiles.on,
+                    grid::TileState::Off => ui_tiles.off,
+                    grid::TileState::Void => ui_tiles.void,
+                },
+            };
+            text.push_str(glyph);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Text of the tiles of the world from `from` to `to`, both included, North on top.
+pub fn world_to_string(
+    world: &mut world::World,
+    from: basics::Position,
+    to: basics::Position,
+    ui_tiles: &grid::UiTiles
+) -> String {
+    let mut text: String = format!(
+        "- View: x={}..{}, y={}..{}; Chunks: {} by {} tiles\n- Legend: on={}, off={}\n",
+        from.x, to.x, from.y, to.y, world.chunk_size, world.chunk_size, ui_tiles.on, ui_tiles.off
+    );
+    for y in (from.y..=to.y).rev() {
+        for x in from.x..=to.x {
+            text.push_str(match world.state_tile(x, y) {
+                grid::TileState::On => ui_tiles.on,
+                grid::TileState::Off => ui_tiles.off,
+                grid::TileState::Void => ui_tiles.void,
+            });
+        }
+        text.push('\n');
+    }
+    text
+}
+
 /// DEBUG - Define kind of info that are available
 #[derive(PartialEq)]
 pub enum DebugLogging {
@@ -33,4 +105,20 @@ pub fn dp(text: String, level_min: DebugLogging) -> () {
             print!("{}", text);
         }
     }
-}
\ No newline at end of file
+}
+
+/// Print the question, then read one line from the standard input; returned trimmed.
+pub fn ask(question: &str) -> String {
+    let mut input: String = String::new();
+    println!("{}", question);
+    io::stdin()
+        .read_line(&mut input)
+        .expect("(X) - Can't read line.");
+    input.trim().to_string()
+}
+
+/// Prevent window of closing, until Enter is pressed.
+pub fn wait_exit() {
+    println!("\nPress Enter to exit... ");
+    let _ = io::stdin().read_line(&mut String::new()).expect("(X) - Can't read line; closing anyway.");
+}
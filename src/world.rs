@@ -0,0 +1,236 @@
+// LABYRINTH
+// World - Endless labyrinth, in chunks generated on demand
+
+use std::collections::VecDeque;
+use super::{HashMap, Rng, SeedableRng, StdRng};
+use super::{basics, generators, grid};
+
+/// Name of the world in the algorithms list.
+pub const NAME: &str = "world";
+/// Description of the world in the algorithms list.
+pub const DESCRIPTION: &str = "Endless labyrinth of random_memory_based chunks, generated on demand; text output of a view.";
+
+/// Mix of the world seed with coordinates, for the seed of a chunk or of an edge between two chunks.
+fn seed_mix(seed: u64, values: [i64; 3]) -> u64 {
+    // SplitMix64 steps, one per value.
+    values.iter().fold(seed, |hash, value| {
+        let mut z: u64 = (hash ^ *value as u64).wrapping_add(0x9E3779B97F4A7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    })
+}
+
+/// # Endless labyrinth; chunks of `chunk_size` by `chunk_size` tiles, each a `grid::Grid`.
+/// A chunk only depends on the world seed and its cords: the same chunk comes out whenever it is generated again.
+/// Each edge between two chunks has one opening, agreed from the seed of the edge, carved on both sides so paths connect.
+/// The chunks last used are kept loaded, up to `capacity`; the least recently used one is dropped first.
+pub struct World {
+    pub seed: u64,
+    pub chunk_size: usize,
+    pub capacity: usize,
+    /// Chunks generated so far, reloads included.
+    pub generated: usize,
+    config: basics::GeneratorConfig,
+    chunks: HashMap<basics::Position, grid::Grid>,
+    /// Loaded chunks, least recently used first.
+    recent: VecDeque<basics::Position>,
+    labyrinth_ui_features: HashMap<grid::TileFeatures, &'static str>,
+}
+
+impl World {
+    /// World of the config: its seed, and `width` as the chunk size; generator settings are those of `random_memory_based`.
+    pub fn new(
+        config: &basics::GeneratorConfig,
+        capacity: usize,
+        labyrinth_ui_features: &HashMap<grid::TileFeatures, &'static str>
+    ) -> Self {
+        // Room for a path, and an opening on each side.
+        let chunk_size: usize = config.width.max(5);
+        World {
+            seed: config.seed,
+            chunk_size,
+            capacity: capacity.max(1),
+            generated: 0,
+            config: config.clone()
+                .width(chunk_size)
+                .height(chunk_size)
+                .mask(None)
+                .start(basics::StartPosition::Centre),
+            chunks: HashMap::new(),
+            recent: VecDeque::new(),
+            labyrinth_ui_features: labyrinth_ui_features.clone(),
+        }
+    }
+
+    /// Chunks currently loaded.
+    pub fn loaded(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Chunk holding the tile at world cords, and the tile cords inside it.
+    pub fn chunk_of(&self, tile: basics::Position) -> (basics::Position, basics::Position) {
+        let size: i32 = self.chunk_size as i32;
+        (
+            basics::Position { x: tile.x.div_euclid(size), y: tile.y.div_euclid(size) },
+            basics::Position { x: tile.x.rem_euclid(size), y: tile.y.rem_euclid(size) },
+        )
+    }
+
+    /// State of the tile at world cords; the world has no edge, so never `Void`.
+    pub fn state_tile(&mut self, x: i32, y: i32) -> grid::TileState {
+        let (chunk, tile) = self.chunk_of(basics::Position { x, y });
+        self.chunk(chunk).state_tile(tile.x, tile.y)
+    }
+
+    /// Grid of the chunk, generated if not loaded; it becomes the most recently used.
+    pub fn chunk(&mut self, chunk: basics::Position) -> &grid::Grid {
+        match self.recent.iter().position(|loaded| *loaded == chunk) {
+            Some(index) => {
+                self.recent.remove(index);
+                self.recent.push_back(chunk);
+            },
+            None => {
+                let grid_chunk: grid::Grid = self.generate(chunk);
+                self.chunks.insert(chunk, grid_chunk);
+                self.recent.push_back(chunk);
+                self.generated += 1usize;
+                while self.recent.len() > self.capacity {
+                    if let Some(oldest) = self.recent.pop_front() {
+                        self.chunks.remove(&oldest);
+                    }
+                }
+            },
+        }
+        &self.chunks[&chunk]
+    }
+
+    /// Tile of the opening along the edge, counted from the South or West end; never a corner.
+    /// Vertical edges are East of their chunk, horizontal ones North of it.
+    fn edge_opening(&self, chunk: basics::Position, vertical: bool) -> i32 {
+        let mut edge_rng: StdRng = StdRng::seed_from_u64(seed_mix(self.seed, [chunk.x as i64, chunk.y as i64, if vertical { 1 } else { 2 }]));
+        edge_rng.random_range(1..self.chunk_size as i32 - 1)
+    }
+
+    /// # Chunk generator; `random_memory_walk` from the centre of the chunk, then the four openings, joined to the path.
+    fn generate(&self, chunk: basics::Position) -> grid::Grid {
+        let config: basics::GeneratorConfig = self.config.clone().seed(seed_mix(self.seed, [chunk.x as i64, chunk.y as i64, 0]));
+        let mut grid_chunk: grid::Grid = generators::grid_initialize(&config, config.default_state);
+        let start: basics::Position = generators::start_tile(&config);
+        grid_chunk.update_tile(start.x, start.y, !config.default_state, Vec::new());
+        generators::random_memory_walk(&mut grid_chunk, start, &config, &self.labyrinth_ui_features, None);
+
+        // Openings, as the wall tile and the tile inward of it.
+        let last: i32 = self.chunk_size as i32 - 1;
+        let openings: [(basics::Position, basics::Position); 4] = {
+            let east: i32 = self.edge_opening(chunk, true);
+            let west: i32 = self.edge_opening(basics::Position { x: chunk.x - 1, y: chunk.y }, true);
+            let north: i32 = self.edge_opening(chunk, false);
+            let south: i32 = self.edge_opening(basics::Position { x: chunk.x, y: chunk.y - 1 }, false);
+            [
+                (basics::Position { x: last, y: east }, basics::Position { x: last - 1, y: east }),
+                (basics::Position { x: 0, y: west }, basics::Position { x: 1, y: west }),
+                (basics::Position { x: north, y: last }, basics::Position { x: north, y: last - 1 }),
+                (basics::Position { x: south, y: 0 }, basics::Position { x: south, y: 1 }),
+            ]
+        };
+        for (wall, inward) in openings {
+            for tile in path_to_nearest(&grid_chunk, &config, inward) {
+                grid_chunk.update_tile(tile.x, tile.y, !config.default_state, Vec::new());
+            }
+            grid_chunk.update_tile(wall.x, wall.y, !config.default_state, Vec::new());
+        }
+        grid_chunk
+    }
+}
+
+/// Walls to carve to join the tile to the nearest path tile, within the outer wall of the chunk; the tile included.
+/// Empty if the tile is a path already.
+fn path_to_nearest(grid_chunk: &grid::Grid, config: &basics::GeneratorConfig, from: basics::Position) -> Vec<basics::Position> {
+    let tiles_max: basics::Position = generators::tiles_max(config);
+    let is_path = |tile: basics::Position| matches!(generators::state_tile(grid_chunk, config, tile.x, tile.y), grid::TileState::On);
+    let mut previous: HashMap<basics::Position, basics::Position> = HashMap::from([(from, from)]);
+    let mut queue: VecDeque<basics::Position> = VecDeque::from([from]);
+    while let Some(tile) = queue.pop_front() {
+        if is_path(tile) {
+            // Back to the tile, the path tile found excluded.
+            let mut walls: Vec<basics::Position> = Vec::new();
+            let mut step: basics::Position = tile;
+            while step != from {
+                step = previous[&step];
+                walls.push(step);
+            }
+            return walls;
+        }
+        for direction in basics::OrdinalDirections::ALL {
+            let offset: basics::Position = direction.offset();
+            let neighbour: basics::Position = basics::Position { x: tile.x + offset.x, y: tile.y + offset.y };
+            if neighbour.x >= 1 && neighbour.y >= 1 && neighbour.x < tiles_max.x && neighbour.y < tiles_max.y
+                && !previous.contains_key(&neighbour)
+            {
+                previous.insert(neighbour, tile);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world_test(capacity: usize) -> World {
+        let config: basics::GeneratorConfig = basics::GeneratorConfig::default().width(12).seed(7);
+        World::new(&config, capacity, &HashMap::new())
+    }
+
+    /// Paths of the chunk, row by row.
+    fn paths(world: &mut World, chunk: basics::Position) -> Vec<bool> {
+        let size: i32 = world.chunk_size as i32;
+        let grid_chunk: &grid::Grid = world.chunk(chunk);
+        (0..size * size)
+            .map(|index| matches!(grid_chunk.state_tile(index % size, index / size), grid::TileState::On))
+            .collect()
+    }
+
+    #[test]
+    fn chunk_same_when_regenerated() {
+        let mut world: World = world_test(1);
+        let chunk: basics::Position = basics::Position { x: 2, y: -3 };
+        let first: Vec<bool> = paths(&mut world, chunk);
+        // Capacity of one: loading another chunk drops the first.
+        paths(&mut world, basics::Position { x: 0, y: 0 });
+        let again: Vec<bool> = paths(&mut world, chunk);
+        assert_eq!(world.generated, 3);
+        assert_eq!(first, again);
+    }
+
+    #[test]
+    fn openings_agree_across_edges() {
+        let mut world: World = world_test(4);
+        let last: i32 = world.chunk_size as i32 - 1;
+        for chunk_x in -2..2 {
+            for chunk_y in -2..2 {
+                let chunk: basics::Position = basics::Position { x: chunk_x, y: chunk_y };
+                let east: basics::Position = basics::Position { x: chunk_x + 1, y: chunk_y };
+                let north: basics::Position = basics::Position { x: chunk_x, y: chunk_y + 1 };
+                let origin_x: i32 = chunk_x * (last + 1);
+                let origin_y: i32 = chunk_y * (last + 1);
+                let mut openings: usize = 0;
+                for along in 0..=last {
+                    // East edge of the chunk, West edge of the next one.
+                    let east_open: bool = matches!(world.state_tile(origin_x + last, origin_y + along), grid::TileState::On);
+                    let west_open: bool = matches!(world.state_tile(origin_x + last + 1, origin_y + along), grid::TileState::On);
+                    assert_eq!(east_open, west_open, "chunks {:?} and {:?}, tile {}", (chunk.x, chunk.y), (east.x, east.y), along);
+                    // North edge of the chunk, South edge of the next one.
+                    let north_open: bool = matches!(world.state_tile(origin_x + along, origin_y + last), grid::TileState::On);
+                    let south_open: bool = matches!(world.state_tile(origin_x + along, origin_y + last + 1), grid::TileState::On);
+                    assert_eq!(north_open, south_open, "chunks {:?} and {:?}, tile {}", (chunk.x, chunk.y), (north.x, north.y), along);
+                    openings += usize::from(east_open) + usize::from(north_open);
+                }
+                assert_eq!(openings, 2);
+            }
+        }
+    }
+}